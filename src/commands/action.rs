@@ -1,11 +1,11 @@
-use crate::util::context::Context;
-use serde::Deserialize;
+use crate::{database::CustomAction, gifs::pool::{fetch_uncached, refill}, util::context::Context};
+use rand::{thread_rng, seq::SliceRandom};
 use std::{str, sync::Arc};
-use hyper::{body::Buf, Uri};
 use twilight_model::{
     application::command::{Command, CommandType},
     application::interaction::{ApplicationCommand, application_command::CommandOptionValue},
-    http::interaction::{InteractionResponse, InteractionResponseType}
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{Id, marker::{ChannelMarker, UserMarker}}
 };
 use twilight_util::builder::{
     command::{CommandBuilder, UserBuilder},
@@ -13,6 +13,7 @@ use twilight_util::builder::{
     InteractionResponseDataBuilder
 };
 
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
 pub enum Action {
     Bite,
     Cuddle,
@@ -30,6 +31,22 @@ pub enum Action {
 }
 
 impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::Bite,
+        Action::Cuddle,
+        Action::Handhold,
+        Action::Hug,
+        Action::Kill,
+        Action::Kiss,
+        Action::Pat,
+        Action::Pinch,
+        Action::Poke,
+        Action::Punch,
+        Action::Shrug,
+        Action::Slap,
+        Action::Tickle
+    ];
+
     pub fn action_phrase(&self) -> &'static str {
         match self {
             Action::Bite => "bites",
@@ -48,6 +65,11 @@ impl Action {
         }
     }
 
+    /// Whether the action's embed shows a GIF. Kills are played out in text by `/kill`.
+    pub fn has_gif(&self) -> bool {
+        !matches!(self, Action::Kill)
+    }
+
     pub fn as_plural(&self) -> &'static str {
         match self {
             Action::Bite => "bites",
@@ -91,13 +113,37 @@ impl Action {
     }
 }
 
-#[derive(Deserialize, Debug)]
-struct OtakuGIFResponse {
-    url: String
+/// The wording of an action, shared by the built-in actions and the ones guilds define themselves.
+struct ActionText<'a> {
+    name: &'a str,
+    phrase: &'a str,
+    plural: &'a str,
+    self_phrase: String
 }
 
-async fn act_and_count(command: ApplicationCommand, context: &Arc<Context>, action: &Action) -> (Option<String>, String, String) {
-    let guild_id = command.guild_id.unwrap();
+impl<'a> From<&'a Action> for ActionText<'a> {
+    fn from(action: &'a Action) -> Self {
+        Self {
+            name: action.as_str(),
+            phrase: action.action_phrase(),
+            plural: action.as_plural(),
+            self_phrase: format!("{} themselves", action.action_phrase())
+        }
+    }
+}
+
+impl<'a> From<&'a CustomAction> for ActionText<'a> {
+    fn from(custom_action: &'a CustomAction) -> Self {
+        Self {
+            name: &custom_action.name,
+            phrase: &custom_action.phrase,
+            plural: &custom_action.plural,
+            self_phrase: custom_action.self_phrase.clone()
+        }
+    }
+}
+
+fn read_participants(command: &ApplicationCommand) -> (Id<UserMarker>, Id<UserMarker>) {
     let member_id = command.author_id().unwrap();
     let recipient_id = match command.data.options.first() {
         Some(option) => match option.value {
@@ -106,48 +152,66 @@ async fn act_and_count(command: ApplicationCommand, context: &Arc<Context>, acti
         }
         None => member_id,
     };
-    let count = context.database().upsert_action(guild_id, member_id, recipient_id, action.as_str()).await;
+
+    (member_id, recipient_id)
+}
+
+fn describe(text: &ActionText, member_id: Id<UserMarker>, recipient_id: Id<UserMarker>, count: u64) -> (Option<String>, String, String) {
     let content = if member_id.eq(&recipient_id) {
         None
     } else {
         Some(format!("<@{recipient_id}>"))
     };
     let description = if member_id.eq(&recipient_id) {
-        format!("*<@{member_id}> {} themselves!*", action.action_phrase())
+        format!("*<@{member_id}> {}!*", text.self_phrase)
     } else {
-        format!("*<@{member_id}> {} you!*", action.action_phrase())
+        format!("*<@{member_id}> {} you!*", text.phrase)
     };
     let footer_text = match count {
         1 =>  if member_id.eq(&recipient_id) {
-            format!("That's your first {} from yourself!", action.as_str())
+            format!("That's your first {} from yourself!", text.name)
         } else {
-            format!("That's their first {} from you!", action.as_str())
+            format!("That's their first {} from you!", text.name)
         },
-        _ => format!("That's {count} {} now!", action.as_plural())
+        _ => format!("That's {count} {} now!", text.plural)
     };
 
     (content, description, footer_text)
 }
 
-async fn get_image_source(context: &Arc<Context>, action: &Action) -> ImageSource {
-    let formatted_uri = format!("https://api.otakugifs.xyz/gif?reaction={}", action.as_str());
-    let uri = formatted_uri.parse::<Uri>().unwrap();
-    let res = context.hyper().get(uri).await.unwrap();
-    let body = hyper::body::aggregate(res).await.unwrap();
-    let OtakuGIFResponse { url } = serde_json::from_reader(body.reader()).unwrap();
+/// Takes a GIF for the action from the pool, only asking the providers directly when the pool ran dry. The embed is
+/// left without an image when no provider has one.
+async fn get_image_source(context: &Arc<Context>, action: Action, channel_id: Id<ChannelMarker>) -> Option<ImageSource> {
+    let url = match context.gif_pool().take(action, channel_id) {
+        Some(url) => Some(url),
+        None => match fetch_uncached(context, action, channel_id).await {
+            Ok(url) => Some(url),
+            Err(error) => {
+                tracing::warn!("Unable to find a GIF for {}: {error}", action.as_str());
+                None
+            }
+        }
+    };
+
+    if context.gif_pool().needs_refill(action) {
+        tokio::spawn(refill(context.clone(), action));
+    }
 
-    ImageSource::url(url).unwrap()
+    url.and_then(|url| ImageSource::url(url).ok())
 }
 
-pub async fn get_interaction_response(command: ApplicationCommand, context: &Arc<Context>, action: Action) -> Result<InteractionResponse, anyhow::Error> {
-    let (content, description, footer_text) = act_and_count(command, &context, &action).await;
-    let image_source = get_image_source(context, &action).await;
-    let embed = EmbedBuilder::new()
+fn create_response(description: (Option<String>, String, String), image_source: Option<ImageSource>) -> InteractionResponse {
+    let (content, description, footer_text) = description;
+    let mut embed = EmbedBuilder::new()
         .color(0xF8F8FF)
         .description(description)
-        .footer(EmbedFooterBuilder::new(footer_text))
-        .image(image_source)
-        .build();
+        .footer(EmbedFooterBuilder::new(footer_text));
+
+    if let Some(image_source) = image_source {
+        embed = embed.image(image_source);
+    }
+
+    let embed = embed.build();
     let interation_response_data = match content {
         Some(content) => InteractionResponseDataBuilder::new()
             .content(content)
@@ -158,5 +222,36 @@ pub async fn get_interaction_response(command: ApplicationCommand, context: &Arc
             .build()
     };
 
-    Ok(InteractionResponse { data: Some(interation_response_data), kind: InteractionResponseType::ChannelMessageWithSource })
-}
\ No newline at end of file
+    InteractionResponse { data: Some(interation_response_data), kind: InteractionResponseType::ChannelMessageWithSource }
+}
+
+pub async fn get_interaction_response(command: ApplicationCommand, context: &Arc<Context>, action: Action) -> Result<InteractionResponse, anyhow::Error> {
+    let guild_id = command.guild_id.unwrap();
+    let (member_id, recipient_id) = read_participants(&command);
+    let count = context.database().create_action_event(guild_id, command.channel_id, member_id, recipient_id, action.as_str()).await?;
+    let description = describe(&ActionText::from(&action), member_id, recipient_id, count);
+    let image_source = get_image_source(context, action, command.channel_id).await;
+
+    Ok(create_response(description, image_source))
+}
+
+/// Runs an action a guild defined with `/customaction`, picking its GIF from the action's own list.
+pub async fn get_custom_interaction_response(command: ApplicationCommand, context: &Arc<Context>, custom_action: CustomAction) -> Result<InteractionResponse, anyhow::Error> {
+    let guild_id = command.guild_id.unwrap();
+    let (member_id, recipient_id) = read_participants(&command);
+    let count = context.database().create_action_event(guild_id, command.channel_id, member_id, recipient_id, &custom_action.name).await?;
+    let description = describe(&ActionText::from(&custom_action), member_id, recipient_id, count);
+    let image_source = custom_action.gif_urls
+        .choose(&mut thread_rng())
+        .and_then(|url| ImageSource::url(url).ok());
+
+    Ok(create_response(description, image_source))
+}
+
+pub fn create_custom_action_command(custom_action: &CustomAction) -> Command {
+    let description = format!("Custom action: {} someone", custom_action.phrase);
+
+    CommandBuilder::new(custom_action.name.clone(), description, CommandType::ChatInput)
+        .option(UserBuilder::new("user".into(), "The recipient".into()))
+        .build()
+}
@@ -0,0 +1,181 @@
+use crate::{
+    commands::Action,
+    util::{context::Context, helper::{create_interaction_response, create_page_buttons}}
+};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::interaction::{ApplicationCommand, message_component::MessageComponentInteraction},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{Id, marker::{GuildMarker, UserMarker}}
+};
+use twilight_util::builder::{
+    embed::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder},
+    InteractionResponseDataBuilder
+};
+
+const PARTNER_LIMIT: i64 = 10;
+const RANKED_ACTIONS: [(Action, &str); 2] = [(Action::Hug, "Most hugged"), (Action::Slap, "Most slapped")];
+const RANKING_LIMIT: i64 = 5;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    desc = "Look back on the actions members shared",
+    dm_permission = false,
+    name = "actions"
+)]
+pub enum ActionsCommand {
+    #[command(name = "stats")]
+    Stats(ActionsStats)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Shows the actions someone gave and received", name = "stats")]
+pub struct ActionsStats {
+    #[command(desc = "Whose actions to show, defaults to you")]
+    user: Option<Id<UserMarker>>,
+    #[command(desc = "Only show the actions exchanged with this member")]
+    with: Option<Id<UserMarker>>
+}
+
+/// The pages of a stats embed. Pair breakdowns skip the partner page, since it would only list the other member.
+enum StatsPage {
+    Actions,
+    Partners,
+    Rankings
+}
+
+fn create_pages(with_id: Option<Id<UserMarker>>) -> Vec<StatsPage> {
+    match with_id {
+        Some(_) => vec![StatsPage::Actions, StatsPage::Rankings],
+        None => vec![StatsPage::Actions, StatsPage::Partners, StatsPage::Rankings]
+    }
+}
+
+async fn describe_actions(context: &Arc<Context>, guild_id: Id<GuildMarker>, user_id: Id<UserMarker>, with_id: Option<Id<UserMarker>>) -> String {
+    let tallies = context.database().read_action_tallies(guild_id, user_id, with_id).await;
+
+    if tallies.is_empty() {
+        return ":pensive: No counted actions...".to_string();
+    }
+
+    let received_from = match with_id {
+        Some(with_id) => format!("from <@{with_id}>"),
+        None => "received".to_string()
+    };
+
+    tallies
+        .iter()
+        .map(|tally| format!("**{}** — {} given · {} {received_from}", tally.action, tally.given, tally.received))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+async fn describe_partners(context: &Arc<Context>, guild_id: Id<GuildMarker>, user_id: Id<UserMarker>) -> String {
+    let partners = context.database().read_action_partners(guild_id, user_id, PARTNER_LIMIT).await;
+
+    if partners.is_empty() {
+        return ":pensive: No partners yet...".to_string();
+    }
+
+    partners
+        .iter()
+        .enumerate()
+        .map(|(index, partner)| format!("**#{}** <@{}> — {} given · {} received", index + 1, partner.partner_id, partner.given, partner.received))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+async fn create_ranking_fields(context: &Arc<Context>, guild_id: Id<GuildMarker>) -> Vec<EmbedFieldBuilder> {
+    let mut fields = Vec::new();
+
+    for (action, name) in RANKED_ACTIONS {
+        let recipients = context.database().read_top_recipients(guild_id, action.as_str(), RANKING_LIMIT).await;
+        let value = match recipients.is_empty() {
+            true => "Nobody yet...".to_string(),
+            false => recipients
+                .iter()
+                .enumerate()
+                .map(|(index, (recipient_id, count))| format!("**#{}** <@{recipient_id}> — {count}", index + 1))
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+
+        fields.push(EmbedFieldBuilder::new(name, value).inline());
+    }
+
+    fields
+}
+
+async fn create_stats_response(
+    context: &Arc<Context>,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    with_id: Option<Id<UserMarker>>,
+    page: usize,
+    response_kind: InteractionResponseType
+) -> InteractionResponse {
+    let pages = create_pages(with_id);
+    let page = page.min(pages.len() - 1);
+    let embed = EmbedBuilder::new()
+        .color(0xF8F8FF)
+        .footer(EmbedFooterBuilder::new(format!("Page {} of {}", page + 1, pages.len())));
+    let embed = match pages[page] {
+        StatsPage::Actions => {
+            let title = match with_id {
+                Some(_) => "Actions between",
+                None => "Actions of"
+            };
+            let description = describe_actions(context, guild_id, user_id, with_id).await;
+            let members = match with_id {
+                Some(with_id) => format!("<@{user_id}> and <@{with_id}>"),
+                None => format!("<@{user_id}>")
+            };
+
+            embed.title(title).description(format!("{members}\n\n{description}"))
+        },
+        StatsPage::Partners => embed
+            .title("Top partners")
+            .description(format!("<@{user_id}>\n\n{}", describe_partners(context, guild_id, user_id).await)),
+        StatsPage::Rankings => create_ranking_fields(context, guild_id)
+            .await
+            .into_iter()
+            .fold(embed.title("Server rankings"), |embed, field| embed.field(field))
+    };
+    let custom_id_prefix = format!("actions:{user_id}:{}", with_id.map_or(0, |with_id| with_id.get()));
+
+    InteractionResponse {
+        data: Some(
+            InteractionResponseDataBuilder::new()
+                .components([create_page_buttons(&custom_id_prefix, page, pages.len())])
+                .embeds([embed.build()])
+                .build()
+        ),
+        kind: response_kind
+    }
+}
+
+impl ActionsCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+        let author_id = command.author_id().unwrap();
+        let ActionsCommand::Stats(ActionsStats { user, with }) = ActionsCommand::from_interaction(command.data.into())?;
+        let user_id = user.unwrap_or(author_id);
+
+        if with == Some(user_id) {
+            return create_interaction_response("Pick two different members to compare!", true);
+        }
+
+        Ok(create_stats_response(context, guild_id, user_id, with, 0, InteractionResponseType::ChannelMessageWithSource).await)
+    }
+
+    pub async fn paginate(component: &MessageComponentInteraction, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = component.guild_id.unwrap();
+        let mut parts = component.data.custom_id.split(':').skip(1);
+        let user_id = parts.next().and_then(|id| Id::new_checked(id.parse().ok()?)).ok_or_else(|| anyhow::anyhow!("Missing user"))?;
+        let with_id = parts.next().and_then(|id| Id::new_checked(id.parse().ok()?));
+        let page = parts.next().and_then(|page| page.parse::<usize>().ok()).unwrap_or(0);
+
+        Ok(create_stats_response(context, guild_id, user_id, with_id, page, InteractionResponseType::UpdateMessage).await)
+    }
+}
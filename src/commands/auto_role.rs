@@ -0,0 +1,151 @@
+use crate::util::{
+    context::Context,
+    helper::create_interaction_response,
+    permission::{check_assignable, has_permission, manage_roles_permission}
+};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::interaction::ApplicationCommand,
+    guild::Permissions,
+    http::interaction::InteractionResponse,
+    id::{Id, marker::RoleMarker}
+};
+
+const MAX_AUTOROLES: usize = 10;
+const MAX_DELAY_MINUTES: i64 = 1440;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    default_permissions = "manage_roles_permission",
+    desc = "Manage the roles new members receive when they join",
+    dm_permission = false,
+    name = "autorole"
+)]
+pub enum AutoRoleCommand {
+    #[command(name = "add")]
+    Add(AutoRoleAdd),
+    #[command(name = "delay")]
+    Delay(AutoRoleDelay),
+    #[command(name = "list")]
+    List(AutoRoleList),
+    #[command(name = "remove")]
+    Remove(AutoRoleRemove),
+    #[command(name = "screening")]
+    Screening(AutoRoleScreening)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Gives a role to every member who joins", name = "add")]
+pub struct AutoRoleAdd {
+    #[command(desc = "The role to give")]
+    role: Id<RoleMarker>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Waits before giving the roles to new members", name = "delay")]
+pub struct AutoRoleDelay {
+    #[command(desc = "How many minutes to wait, 0 to give them right away", max_value = 1440, min_value = 0)]
+    minutes: i64
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Shows the roles new members receive", name = "list")]
+pub struct AutoRoleList {}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Stops giving a role to new members", name = "remove")]
+pub struct AutoRoleRemove {
+    #[command(desc = "The role to stop giving")]
+    role: Id<RoleMarker>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Waits until new members pass membership screening before giving the roles", name = "screening")]
+pub struct AutoRoleScreening {
+    #[command(desc = "Whether to wait for membership screening")]
+    enabled: bool
+}
+
+impl AutoRoleCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+
+        if !has_permission(command.member.as_ref(), Permissions::MANAGE_ROLES) {
+            return create_interaction_response("You need the **Manage Roles** permission to manage autoroles.", true);
+        }
+
+        let setting = match context.database().read_setting(guild_id).await {
+            Some(setting) => setting,
+            None => return create_interaction_response("Unable to find this server's settings.", true)
+        };
+        let mut member_role_ids = setting.member_role_ids;
+        let options = AutoRoleCommand::from_interaction(command.data.into())?;
+
+        match options {
+            AutoRoleCommand::Add(AutoRoleAdd { role }) => {
+                if role == guild_id.cast() {
+                    return create_interaction_response("Everyone already has the @everyone role!", true);
+                }
+
+                if member_role_ids.contains(&role) {
+                    return create_interaction_response(&format!("<@&{role}> is already an autorole!"), true);
+                }
+
+                if member_role_ids.len() >= MAX_AUTOROLES {
+                    return create_interaction_response(&format!("You can only have up to {MAX_AUTOROLES} autoroles!"), true);
+                }
+
+                if let Err(error) = check_assignable(context, guild_id, role) {
+                    return create_interaction_response(&error.to_string(), true);
+                }
+
+                member_role_ids.push(role);
+                context.database().update_member_role_ids(guild_id, &member_role_ids).await;
+                create_interaction_response(&format!("New members will now receive <@&{role}>."), true)
+            },
+            AutoRoleCommand::Delay(AutoRoleDelay { minutes }) => {
+                let minutes = minutes.clamp(0, MAX_DELAY_MINUTES);
+
+                context.database().update_member_role_delay(guild_id, (minutes * 60) as u32).await;
+
+                match minutes {
+                    0 => create_interaction_response("New members will receive their roles right away.", true),
+                    _ => create_interaction_response(&format!("New members will receive their roles after **{minutes}** minute(s)."), true)
+                }
+            },
+            AutoRoleCommand::List(_) => {
+                let roles = match member_role_ids.is_empty() {
+                    true => "None".to_string(),
+                    false => member_role_ids.iter().map(|role_id| format!("<@&{role_id}>")).collect::<Vec<_>>().join(", ")
+                };
+                let description = format!(
+                    "**Roles:** {roles}\n**Delay:** {} minute(s)\n**Wait for screening:** {}",
+                    setting.member_role_delay / 60,
+                    if setting.member_role_after_screening { "Yes" } else { "No" }
+                );
+
+                create_interaction_response(&description, true)
+            },
+            AutoRoleCommand::Remove(AutoRoleRemove { role }) => {
+                if !member_role_ids.contains(&role) {
+                    return create_interaction_response(&format!("<@&{role}> is not an autorole!"), true);
+                }
+
+                member_role_ids.retain(|role_id| *role_id != role);
+                context.database().update_member_role_ids(guild_id, &member_role_ids).await;
+                create_interaction_response(&format!("New members will no longer receive <@&{role}>."), true)
+            },
+            AutoRoleCommand::Screening(AutoRoleScreening { enabled }) => {
+                context.database().update_member_role_after_screening(guild_id, enabled).await;
+
+                let description = match enabled {
+                    true => "New members will receive their roles once they pass membership screening.",
+                    false => "New members will receive their roles as soon as they join."
+                };
+
+                create_interaction_response(description, true)
+            }
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{
+    commands::{keep_roles::toggle_keep_roles, shared_role::parse_color},
+    database::setting::Module,
+    levels::LevelCurve,
+    util::{context::Context, helper::{create_interaction_response, refresh_guild_commands}, permission::{has_permission, manage_guild_permission}}
+};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_model::{
+    application::interaction::ApplicationCommand,
+    channel::message::MessageFlags,
+    guild::Permissions,
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{Id, marker::{ChannelMarker, GuildMarker}}
+};
+use twilight_util::builder::{embed::{EmbedBuilder, EmbedFieldBuilder}, InteractionResponseDataBuilder};
+
+#[derive(CommandOption, CreateOption)]
+pub enum ModuleChoice {
+    #[option(name = "Actions", value = "actions")]
+    Actions,
+    #[option(name = "Levels", value = "levels")]
+    Levels,
+    #[option(name = "Shared roles", value = "shared_roles")]
+    SharedRoles
+}
+
+#[derive(CommandOption, CreateOption)]
+pub enum CurveChoice {
+    #[option(name = "Linear (100 XP per level)", value = "linear")]
+    Linear,
+    #[option(name = "Quadratic (100 XP times level squared)", value = "quadratic")]
+    Quadratic,
+    #[option(name = "Polynomial (MEE6 style)", value = "polynomial")]
+    Polynomial,
+    #[option(name = "Custom table", value = "table")]
+    Table
+}
+
+impl From<ModuleChoice> for Module {
+    fn from(module: ModuleChoice) -> Self {
+        match module {
+            ModuleChoice::Actions => Module::Actions,
+            ModuleChoice::Levels => Module::Levels,
+            ModuleChoice::SharedRoles => Module::SharedRoles
+        }
+    }
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    default_permissions = "manage_guild_permission",
+    desc = "Manage Aurora's settings for this server",
+    dm_permission = false,
+    name = "config"
+)]
+pub enum ConfigCommand {
+    #[command(name = "curve")]
+    Curve(ConfigCurve),
+    #[command(name = "hidecommands")]
+    HideCommands(ConfigHideCommands),
+    #[command(name = "keeproles")]
+    KeepRoles(ConfigKeepRoles),
+    #[command(name = "logchannel")]
+    LogChannel(ConfigLogChannel),
+    #[command(name = "messagelevels")]
+    MessageLevels(ConfigMessageLevels),
+    #[command(name = "module")]
+    Module(ConfigModule),
+    #[command(name = "rankcolor")]
+    RankColor(ConfigRankColor),
+    #[command(name = "view")]
+    View(ConfigView),
+    #[command(name = "voicelevels")]
+    VoiceLevels(ConfigVoiceLevels)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Sets how much XP each level takes", name = "curve")]
+pub struct ConfigCurve {
+    #[command(desc = "The level curve")]
+    curve: CurveChoice,
+    #[command(desc = "For custom tables, the total XP for levels 1, 2, 3... separated by commas")]
+    table: Option<String>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Hides this server's custom actions while the actions module is disabled", name = "hidecommands")]
+pub struct ConfigHideCommands {
+    #[command(desc = "Whether custom actions are hidden while actions are disabled")]
+    enabled: bool
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Turns role persistence for returning members on or off", name = "keeproles")]
+pub struct ConfigKeepRoles {
+    #[command(desc = "Whether returning members get their roles back")]
+    enabled: bool
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Sets where Aurora posts notices, defaulting to the system channel", name = "logchannel")]
+pub struct ConfigLogChannel {
+    #[command(channel_types = "guild_text guild_news", desc = "The log channel, leave empty to use the system channel")]
+    channel: Option<Id<ChannelMarker>>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Turns XP for messages on or off", name = "messagelevels")]
+pub struct ConfigMessageLevels {
+    #[command(desc = "Whether messages earn XP")]
+    enabled: bool
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Turns a module on or off", name = "module")]
+pub struct ConfigModule {
+    #[command(desc = "The module")]
+    module: ModuleChoice,
+    #[command(desc = "Whether the module is enabled")]
+    enabled: bool
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Sets the accent color of rank cards and leaderboards", name = "rankcolor")]
+pub struct ConfigRankColor {
+    #[command(desc = "The color as a hex code, like #FFB6C1")]
+    color: String
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Shows the current settings", name = "view")]
+pub struct ConfigView {}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Turns XP for time in voice channels on or off", name = "voicelevels")]
+pub struct ConfigVoiceLevels {
+    #[command(desc = "Whether voice channels earn XP")]
+    enabled: bool
+}
+
+fn describe_state(enabled: bool) -> &'static str {
+    if enabled { "Enabled" } else { "Disabled" }
+}
+
+async fn create_view_response(context: &Arc<Context>, guild_id: Id<GuildMarker>) -> Result<InteractionResponse, anyhow::Error> {
+    let setting = match context.database().read_setting(guild_id).await {
+        Some(setting) => setting,
+        None => return create_interaction_response("Unable to find this server's settings.", true)
+    };
+    let modules = [Module::Actions, Module::Levels, Module::SharedRoles]
+        .iter()
+        .map(|module| format!("{}: {}", module.as_str(), describe_state(setting.is_enabled(*module))))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let curve = match &setting.level_curve {
+        LevelCurve::Table(table) => format!("table ({} levels)", table.len()),
+        curve => curve.as_str().to_string()
+    };
+    let log_channel = match setting.log_channel_id {
+        Some(channel_id) => format!("<#{channel_id}>"),
+        None => "System channel".to_string()
+    };
+    let embed = EmbedBuilder::new()
+        .color(setting.rank_color)
+        .title("Settings")
+        .field(EmbedFieldBuilder::new("Modules", modules))
+        .field(EmbedFieldBuilder::new("Message levels", describe_state(setting.message_levels_enabled)).inline())
+        .field(EmbedFieldBuilder::new("Voice levels", describe_state(setting.voice_levels_enabled)).inline())
+        .field(EmbedFieldBuilder::new("Level curve", curve).inline())
+        .field(EmbedFieldBuilder::new("Rank color", format!("#{:06X}", setting.rank_color)).inline())
+        .field(EmbedFieldBuilder::new("Keep roles", describe_state(setting.should_keep_roles)).inline())
+        .field(EmbedFieldBuilder::new("Log channel", log_channel).inline())
+        .field(EmbedFieldBuilder::new("Autoroles", setting.member_role_ids.len().to_string()).inline())
+        .field(EmbedFieldBuilder::new("Hide disabled commands", describe_state(setting.hide_disabled_commands)).inline())
+        .build();
+
+    Ok(
+        InteractionResponse {
+            data: Some(
+                InteractionResponseDataBuilder::new()
+                    .embeds([embed])
+                    .flags(MessageFlags::EPHEMERAL)
+                    .build()
+            ),
+            kind: InteractionResponseType::ChannelMessageWithSource
+        }
+    )
+}
+
+impl ConfigCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+
+        if !has_permission(command.member.as_ref(), Permissions::MANAGE_GUILD) {
+            return create_interaction_response("You need the **Manage Server** permission to change settings.", true);
+        }
+
+        let options = ConfigCommand::from_interaction(command.data.into())?;
+
+        match options {
+            ConfigCommand::Curve(ConfigCurve { curve, table }) => {
+                let curve = match (curve, table) {
+                    (CurveChoice::Linear, _) => LevelCurve::Linear,
+                    (CurveChoice::Quadratic, _) => LevelCurve::Quadratic,
+                    (CurveChoice::Polynomial, _) => LevelCurve::Polynomial,
+                    (CurveChoice::Table, Some(table)) => match LevelCurve::parse_table(&table) {
+                        Some(table) => LevelCurve::Table(table),
+                        None => return create_interaction_response("Tables must be increasing amounts of XP separated by commas, like `100, 250, 500`!", true)
+                    },
+                    (CurveChoice::Table, None) => return create_interaction_response("Custom tables need the `table` option!", true)
+                };
+
+                context.database().update_level_curve(guild_id, &curve).await;
+                create_interaction_response(&format!("Levels now follow the **{}** curve. Run `/levelrole sync` to update level roles.", curve.as_str()), true)
+            },
+            ConfigCommand::HideCommands(ConfigHideCommands { enabled }) => {
+                context.database().update_hide_disabled_commands(guild_id, enabled).await;
+                refresh_guild_commands(context, guild_id).await;
+                create_interaction_response(&format!("Hiding custom actions while actions are disabled is now **{}**.", describe_state(enabled).to_lowercase()), true)
+            },
+            ConfigCommand::KeepRoles(ConfigKeepRoles { enabled }) => toggle_keep_roles(context, guild_id, enabled).await,
+            ConfigCommand::LogChannel(ConfigLogChannel { channel }) => {
+                context.database().update_log_channel_id(guild_id, channel).await;
+
+                match channel {
+                    Some(channel_id) => create_interaction_response(&format!("Notices will be posted in <#{channel_id}>."), true),
+                    None => create_interaction_response("Notices will be posted in the system channel.", true)
+                }
+            },
+            ConfigCommand::MessageLevels(ConfigMessageLevels { enabled }) => {
+                context.database().update_message_levels_enabled(guild_id, enabled).await;
+                create_interaction_response(&format!("Message levels are now **{}**.", describe_state(enabled).to_lowercase()), true)
+            },
+            ConfigCommand::Module(ConfigModule { module, enabled }) => {
+                let module = Module::from(module);
+                let mut enabled_modules = match context.database().read_setting(guild_id).await {
+                    Some(setting) => setting.enabled_modules,
+                    None => return create_interaction_response("Unable to find this server's settings.", true)
+                };
+
+                enabled_modules.retain(|enabled_module| *enabled_module != module);
+
+                if enabled {
+                    enabled_modules.push(module);
+                }
+
+                context.database().update_enabled_modules(guild_id, &enabled_modules).await;
+                refresh_guild_commands(context, guild_id).await;
+                create_interaction_response(&format!("The **{}** module is now **{}**.", module.as_str(), describe_state(enabled).to_lowercase()), true)
+            },
+            ConfigCommand::RankColor(ConfigRankColor { color }) => match parse_color(&color) {
+                Some(color) => {
+                    context.database().update_rank_color(guild_id, color).await;
+                    create_interaction_response(&format!("The rank color is now **#{color:06X}**."), true)
+                },
+                None => create_interaction_response("Colors must be hex codes, like #FFB6C1!", true)
+            },
+            ConfigCommand::View(_) => create_view_response(context, guild_id).await,
+            ConfigCommand::VoiceLevels(ConfigVoiceLevels { enabled }) => {
+                context.database().update_voice_levels_enabled(guild_id, enabled).await;
+                create_interaction_response(&format!("Voice levels are now **{}**.", describe_state(enabled).to_lowercase()), true)
+            }
+        }
+    }
+}
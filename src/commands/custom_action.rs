@@ -0,0 +1,195 @@
+use crate::{
+    database::CustomAction,
+    util::{
+        context::Context,
+        helper::{create_interaction_response, is_builtin_command, refresh_guild_commands},
+        permission::{has_permission, manage_guild_permission}
+    }
+};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::interaction::ApplicationCommand,
+    guild::Permissions,
+    http::interaction::InteractionResponse
+};
+use twilight_util::builder::embed::ImageSource;
+
+const MAX_CUSTOM_ACTIONS: usize = 25;
+const MAX_GIF_URLS: usize = 20;
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_PHRASE_LENGTH: usize = 60;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    default_permissions = "manage_guild_permission",
+    desc = "Manage this server's own actions",
+    dm_permission = false,
+    name = "customaction"
+)]
+pub enum CustomActionCommand {
+    #[command(name = "create")]
+    Create(CustomActionCreate),
+    #[command(name = "delete")]
+    Delete(CustomActionDelete),
+    #[command(name = "gifs")]
+    Gifs(CustomActionGifs),
+    #[command(name = "list")]
+    List(CustomActionList)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Creates an action with its own command", name = "create")]
+pub struct CustomActionCreate {
+    #[command(desc = "The command name, like boop")]
+    name: String,
+    #[command(desc = "What the action does to someone, like boops")]
+    phrase: String,
+    #[command(desc = "The plural used when counting, like boops")]
+    plural: String,
+    #[command(desc = "What the action does to yourself, defaults to \"<phrase> themselves\"")]
+    self_phrase: Option<String>,
+    #[command(desc = "GIF URLs separated by spaces")]
+    gifs: Option<String>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Deletes an action along with its counts", name = "delete")]
+pub struct CustomActionDelete {
+    #[command(desc = "The command name")]
+    name: String
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Replaces the GIFs of an action", name = "gifs")]
+pub struct CustomActionGifs {
+    #[command(desc = "The command name")]
+    name: String,
+    #[command(desc = "GIF URLs separated by spaces, leave empty to remove them")]
+    gifs: Option<String>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Lists this server's actions", name = "list")]
+pub struct CustomActionList {}
+
+/// Command names have to follow Discord's rules for chat commands and cannot shadow a built-in command.
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err(format!("Names must be between 1 and {MAX_NAME_LENGTH} characters!"));
+    }
+
+    if !name.chars().all(|character| character.is_ascii_lowercase() || character.is_ascii_digit() || character == '-' || character == '_') {
+        return Err("Names can only use lowercase letters, numbers, dashes and underscores!".to_string());
+    }
+
+    if is_builtin_command(name) {
+        return Err(format!("`/{name}` is already one of Aurora's commands!"));
+    }
+
+    Ok(())
+}
+
+fn parse_gif_urls(gifs: Option<String>) -> Result<Vec<String>, String> {
+    let urls = gifs
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|url| url.to_string())
+        .collect::<Vec<_>>();
+
+    if urls.len() > MAX_GIF_URLS {
+        return Err(format!("Actions can have up to {MAX_GIF_URLS} GIFs!"));
+    }
+
+    match urls.iter().find(|url| !url.starts_with("https://") || ImageSource::url(url.as_str()).is_err()) {
+        Some(url) => Err(format!("`{url}` is not a valid HTTPS URL!")),
+        None => Ok(urls)
+    }
+}
+
+impl CustomActionCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+
+        if !has_permission(command.member.as_ref(), Permissions::MANAGE_GUILD) {
+            return create_interaction_response("You need the **Manage Server** permission to manage actions.", true);
+        }
+
+        let options = CustomActionCommand::from_interaction(command.data.into())?;
+
+        match options {
+            CustomActionCommand::Create(CustomActionCreate { name, phrase, plural, self_phrase, gifs }) => {
+                let name = name.trim().to_lowercase();
+                let phrase = phrase.trim().to_string();
+                let plural = plural.trim().to_string();
+                let self_phrase = self_phrase
+                    .map(|self_phrase| self_phrase.trim().to_string())
+                    .unwrap_or_else(|| format!("{phrase} themselves"));
+
+                if let Err(description) = validate_name(&name) {
+                    return create_interaction_response(&description, true);
+                }
+
+                if [&phrase, &plural, &self_phrase].iter().any(|text| text.is_empty() || text.len() > MAX_PHRASE_LENGTH) {
+                    return create_interaction_response(&format!("Phrases must be between 1 and {MAX_PHRASE_LENGTH} characters!"), true);
+                }
+
+                let gif_urls = match parse_gif_urls(gifs) {
+                    Ok(gif_urls) => gif_urls,
+                    Err(description) => return create_interaction_response(&description, true)
+                };
+
+                if context.database().read_custom_actions(guild_id).await.len() >= MAX_CUSTOM_ACTIONS {
+                    return create_interaction_response(&format!("Servers can have up to {MAX_CUSTOM_ACTIONS} actions!"), true);
+                }
+
+                let custom_action = CustomAction { guild_id, name, phrase, plural, self_phrase, gif_urls };
+
+                if !context.database().create_custom_action(&custom_action).await {
+                    return create_interaction_response(&format!("`/{}` already exists!", custom_action.name), true);
+                }
+
+                refresh_guild_commands(context, guild_id).await;
+                create_interaction_response(&format!("Created `/{}`! It may take a moment to show up.", custom_action.name), true)
+            },
+            CustomActionCommand::Delete(CustomActionDelete { name }) => {
+                let name = name.trim().to_lowercase();
+
+                if !context.database().delete_custom_action(guild_id, &name).await {
+                    return create_interaction_response(&format!("`/{name}` is not one of this server's actions!"), true);
+                }
+
+                refresh_guild_commands(context, guild_id).await;
+                create_interaction_response(&format!("Deleted `/{name}` and its counts."), true)
+            },
+            CustomActionCommand::Gifs(CustomActionGifs { name, gifs }) => {
+                let name = name.trim().to_lowercase();
+                let gif_urls = match parse_gif_urls(gifs) {
+                    Ok(gif_urls) => gif_urls,
+                    Err(description) => return create_interaction_response(&description, true)
+                };
+
+                if !context.database().update_custom_action_gif_urls(guild_id, &name, &gif_urls).await {
+                    return create_interaction_response(&format!("`/{name}` is not one of this server's actions!"), true);
+                }
+
+                create_interaction_response(&format!("`/{name}` now has {} GIF(s).", gif_urls.len()), true)
+            },
+            CustomActionCommand::List(_) => {
+                let custom_actions = context.database().read_custom_actions(guild_id).await;
+
+                if custom_actions.is_empty() {
+                    return create_interaction_response("This server has no actions of its own yet...", true);
+                }
+
+                let description = custom_actions
+                    .iter()
+                    .map(|custom_action| format!("`/{}` {} someone ({} GIFs)", custom_action.name, custom_action.phrase, custom_action.gif_urls.len()))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                create_interaction_response(&description, true)
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::util::{
+    context::Context,
+    helper::create_interaction_response,
+    permission::{has_permission, manage_guild_permission}
+};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::interaction::ApplicationCommand,
+    guild::Permissions,
+    http::interaction::InteractionResponse,
+    id::{Id, marker::{GuildMarker, RoleMarker}}
+};
+
+const MAX_DENIED_ROLES: usize = 25;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    default_permissions = "manage_guild_permission",
+    desc = "Manage role persistence for returning members",
+    dm_permission = false,
+    name = "keeproles"
+)]
+pub enum KeepRolesCommand {
+    #[command(name = "allow")]
+    Allow(KeepRolesAllow),
+    #[command(name = "deny")]
+    Deny(KeepRolesDeny),
+    #[command(name = "show")]
+    Show(KeepRolesShow),
+    #[command(name = "toggle")]
+    Toggle(KeepRolesToggle)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Lets a denied role be restored again", name = "allow")]
+pub struct KeepRolesAllow {
+    #[command(desc = "The role to allow")]
+    role: Id<RoleMarker>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Stops a role from being restored when members return", name = "deny")]
+pub struct KeepRolesDeny {
+    #[command(desc = "The role to deny")]
+    role: Id<RoleMarker>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Shows the role persistence settings", name = "show")]
+pub struct KeepRolesShow {}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Turns role persistence on or off", name = "toggle")]
+pub struct KeepRolesToggle {
+    #[command(desc = "Whether returning members get their roles back")]
+    enabled: bool
+}
+
+/// Turns role persistence on or off, shared with `/config keeproles`.
+pub async fn toggle_keep_roles(context: &Arc<Context>, guild_id: Id<GuildMarker>, enabled: bool) -> Result<InteractionResponse, anyhow::Error> {
+    context.database().update_should_keep_roles(guild_id, enabled).await;
+
+    let description = match enabled {
+        true => "Members who leave will get their roles back when they return.",
+        false => "Members who leave will no longer get their roles back."
+    };
+
+    create_interaction_response(description, true)
+}
+
+impl KeepRolesCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+
+        if !has_permission(command.member.as_ref(), Permissions::MANAGE_GUILD) {
+            return create_interaction_response("You need the **Manage Server** permission to manage role persistence.", true);
+        }
+
+        let setting = match context.database().read_setting(guild_id).await {
+            Some(setting) => setting,
+            None => return create_interaction_response("Unable to find this server's settings.", true)
+        };
+        let mut denied_ids = setting.kept_role_denied_ids;
+        let options = KeepRolesCommand::from_interaction(command.data.into())?;
+
+        match options {
+            KeepRolesCommand::Allow(KeepRolesAllow { role }) => {
+                if !denied_ids.contains(&role) {
+                    return create_interaction_response(&format!("<@&{role}> is not on the deny-list!"), true);
+                }
+
+                denied_ids.retain(|role_id| *role_id != role);
+                context.database().update_kept_role_denied_ids(guild_id, &denied_ids).await;
+                create_interaction_response(&format!("<@&{role}> will be restored to returning members again."), true)
+            },
+            KeepRolesCommand::Deny(KeepRolesDeny { role }) => {
+                if role == guild_id.cast() {
+                    return create_interaction_response("The @everyone role is never restored!", true);
+                }
+
+                if denied_ids.contains(&role) {
+                    return create_interaction_response(&format!("<@&{role}> is already on the deny-list!"), true);
+                }
+
+                if denied_ids.len() >= MAX_DENIED_ROLES {
+                    return create_interaction_response(&format!("You can only deny up to {MAX_DENIED_ROLES} roles!"), true);
+                }
+
+                denied_ids.push(role);
+                context.database().update_kept_role_denied_ids(guild_id, &denied_ids).await;
+                create_interaction_response(&format!("<@&{role}> will no longer be restored to returning members."), true)
+            },
+            KeepRolesCommand::Show(_) => {
+                let denied = match denied_ids.is_empty() {
+                    true => "None".to_string(),
+                    false => denied_ids.iter().map(|role_id| format!("<@&{role_id}>")).collect::<Vec<_>>().join(", ")
+                };
+                let description = format!(
+                    "**Enabled:** {}\n**Denied roles:** {denied}",
+                    if setting.should_keep_roles { "Yes" } else { "No" }
+                );
+
+                create_interaction_response(&description, true)
+            },
+            KeepRolesCommand::Toggle(KeepRolesToggle { enabled }) => toggle_keep_roles(context, guild_id, enabled).await
+        }
+    }
+}
@@ -20,6 +20,7 @@ impl KillCommand {
     pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
         let guild_id = command.guild_id.unwrap();
         let member_id = command.author_id().unwrap();
+        let channel_id = command.channel_id;
         let member_name = command.member.unwrap().user.unwrap().name;
         let KillCommand { target } = KillCommand::from_interaction(command.data.into())?;
         let User { id: target_id, name: target_name, .. } = target.resolved;
@@ -40,8 +41,8 @@ impl KillCommand {
             let index = rand::thread_rng().gen_range(0..strings.len());
             let result = strings.into_iter().nth(index).unwrap();
     
-            if result.1.is_some() && result.2.is_some() {
-                context.database().upsert_action(guild_id, result.1.unwrap(), result.2.unwrap(), Action::Kill.as_str()).await;
+            if let (Some(winner_id), Some(loser_id)) = (result.1, result.2) {
+                context.database().create_action_event(guild_id, channel_id, winner_id, loser_id, Action::Kill.as_str()).await?;
             }
 
             let (member_kill_count, target_kill_count) = context.database().read_kill_counts(guild_id, member_id, target_id).await;
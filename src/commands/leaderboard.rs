@@ -0,0 +1,136 @@
+use crate::{
+    commands::level_role::LevelKind,
+    database::{Setting, level_role::RoleKind},
+    util::{context::Context, helper::{create_interaction_response, create_page_buttons}}
+};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::{
+        component::{button::{Button, ButtonStyle}, Component},
+        interaction::{ApplicationCommand, message_component::MessageComponentInteraction}
+    },
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{Id, marker::{GuildMarker, UserMarker}}
+};
+use twilight_util::builder::{embed::{EmbedBuilder, EmbedFooterBuilder}, InteractionResponseDataBuilder};
+
+const PAGE_SIZE: u64 = 10;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    desc = "Shows the server's top members",
+    dm_permission = false,
+    name = "leaderboard"
+)]
+pub struct LeaderboardCommand {
+    #[command(desc = "Which XP to rank by")]
+    kind: LevelKind
+}
+
+async fn create_leaderboard_response(context: &Arc<Context>, setting: &Setting, kind: RoleKind, page: u64, response_kind: InteractionResponseType) -> InteractionResponse {
+    let guild_id = setting.guild_id;
+    let size = context.database().read_leaderboard_size(guild_id, kind).await;
+    let page_count = size.div_ceil(PAGE_SIZE).max(1);
+    let page = page.min(page_count - 1);
+    let members = context.database().read_leaderboard(guild_id, kind, PAGE_SIZE, page * PAGE_SIZE).await;
+    let description = if members.is_empty() {
+        ":pensive: Nobody has earned any XP yet...".to_string()
+    } else {
+        members
+            .iter()
+            .enumerate()
+            .map(|(index, member)| {
+                let xp = match kind {
+                    RoleKind::Message => member.message_xp,
+                    RoleKind::Voice => member.voice_xp
+                };
+                let position = page * PAGE_SIZE + index as u64 + 1;
+
+                format!("**#{position}** <@{}> — LVL {} · {xp} XP", member.member_id, setting.level_curve.level_for_xp(xp))
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    let title = match kind {
+        RoleKind::Message => "Message leaderboard",
+        RoleKind::Voice => "Voice leaderboard"
+    };
+    let embed = EmbedBuilder::new()
+        .color(setting.rank_color)
+        .description(description)
+        .footer(EmbedFooterBuilder::new(format!("Page {} of {page_count}", page + 1)))
+        .title(title)
+        .build();
+    let custom_id_prefix = format!("leaderboard:{}", kind.as_str());
+    let mut buttons = create_page_buttons(&custom_id_prefix, page as usize, page_count as usize);
+
+    if let Component::ActionRow(action_row) = &mut buttons {
+        action_row.components.push(Component::Button(Button {
+            custom_id: Some(format!("{custom_id_prefix}:me")),
+            disabled: false,
+            emoji: None,
+            label: Some("Jump to me".into()),
+            style: ButtonStyle::Primary,
+            url: None
+        }));
+    }
+
+    InteractionResponse {
+        data: Some(
+            InteractionResponseDataBuilder::new()
+                .components([buttons])
+                .embeds([embed])
+                .build()
+        ),
+        kind: response_kind
+    }
+}
+
+async fn read_own_page(context: &Arc<Context>, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>, kind: RoleKind) -> Option<u64> {
+    let (message_xp, _, voice_xp) = context.database().read_xp(guild_id, member_id).await?;
+    let xp = match kind {
+        RoleKind::Message => message_xp,
+        RoleKind::Voice => voice_xp
+    };
+
+    if xp == 0 {
+        None
+    } else {
+        Some((context.database().read_rank(guild_id, member_id, kind).await - 1) / PAGE_SIZE)
+    }
+}
+
+impl LeaderboardCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+        let LeaderboardCommand { kind } = LeaderboardCommand::from_interaction(command.data.into())?;
+
+        match context.database().read_setting(guild_id).await {
+            Some(setting) => Ok(create_leaderboard_response(context, &setting, kind.into(), 0, InteractionResponseType::ChannelMessageWithSource).await),
+            None => create_interaction_response("Unable to find this server's settings.", true)
+        }
+    }
+
+    pub async fn paginate(component: &MessageComponentInteraction, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = component.guild_id.unwrap();
+        let mut parts = component.data.custom_id.split(':').skip(1);
+        let kind = match parts.next() {
+            Some("voice") => RoleKind::Voice,
+            _ => RoleKind::Message
+        };
+        let setting = match context.database().read_setting(guild_id).await {
+            Some(setting) => setting,
+            None => return create_interaction_response("Unable to find this server's settings.", true)
+        };
+        let page = match parts.next() {
+            Some("me") => match read_own_page(context, guild_id, component.author_id().unwrap(), kind).await {
+                Some(page) => page,
+                None => return create_interaction_response("You are not on this leaderboard yet!", true)
+            },
+            page => page.and_then(|page| page.parse::<u64>().ok()).unwrap_or(0)
+        };
+
+        Ok(create_leaderboard_response(context, &setting, kind, page, InteractionResponseType::UpdateMessage).await)
+    }
+}
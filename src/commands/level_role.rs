@@ -0,0 +1,206 @@
+use crate::{
+    database::{LevelRole, level_role::RoleKind},
+    levels::sync::start_sync,
+    util::{
+        context::Context,
+        helper::{create_interaction_response, create_page_buttons},
+        permission::{check_assignable, has_permission, manage_roles_permission}
+    }
+};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_model::{
+    application::interaction::{ApplicationCommand, message_component::MessageComponentInteraction},
+    guild::{Permissions, Role},
+    http::interaction::{InteractionResponse, InteractionResponseType}
+};
+use twilight_util::builder::{embed::{EmbedBuilder, EmbedFooterBuilder}, InteractionResponseDataBuilder};
+
+const PAGE_SIZE: usize = 10;
+
+#[derive(CommandOption, CreateOption)]
+pub enum LevelKind {
+    #[option(name = "Message", value = "message")]
+    Message,
+    #[option(name = "Voice", value = "voice")]
+    Voice
+}
+
+impl From<LevelKind> for RoleKind {
+    fn from(kind: LevelKind) -> Self {
+        match kind {
+            LevelKind::Message => RoleKind::Message,
+            LevelKind::Voice => RoleKind::Voice
+        }
+    }
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    default_permissions = "manage_roles_permission",
+    desc = "Manage level roles",
+    dm_permission = false,
+    name = "levelrole"
+)]
+pub enum LevelRoleCommand {
+    #[command(name = "add")]
+    Add(LevelRoleAdd),
+    #[command(name = "clear")]
+    Clear(LevelRoleClear),
+    #[command(name = "list")]
+    List(LevelRoleList),
+    #[command(name = "remove")]
+    Remove(LevelRoleRemove),
+    #[command(name = "sync")]
+    Sync(LevelRoleSync)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Grants a role once members reach a level", name = "add")]
+pub struct LevelRoleAdd {
+    #[command(desc = "The role to grant")]
+    role: Role,
+    #[command(desc = "Whether message or voice XP counts towards this role")]
+    kind: LevelKind,
+    #[command(desc = "The level to grant the role at", max_value = 65535, min_value = 1)]
+    level: i64,
+    #[command(desc = "Whether members keep this role after reaching higher tiers")]
+    persistent: Option<bool>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Removes every level role", name = "clear")]
+pub struct LevelRoleClear {}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Lists the level roles", name = "list")]
+pub struct LevelRoleList {}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Stops granting a role for levels", name = "remove")]
+pub struct LevelRoleRemove {
+    #[command(desc = "The role to stop granting")]
+    role: Role
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Brings every member's level roles in line with their XP", name = "sync")]
+pub struct LevelRoleSync {}
+
+fn create_list_response(level_roles: &[LevelRole], page: usize, kind: InteractionResponseType) -> InteractionResponse {
+    let page_count = level_roles.len().div_ceil(PAGE_SIZE).max(1);
+    let page = page.min(page_count - 1);
+    let description = level_roles
+        .iter()
+        .skip(page * PAGE_SIZE)
+        .take(PAGE_SIZE)
+        .map(|level_role| {
+            let persistence = if level_role.is_persistent { "persistent" } else { "replaced by higher tiers" };
+
+            format!("**Level {}** ({}) <@&{}> — {persistence}", level_role.level, level_role.kind.as_str(), level_role.role_id)
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    let embed = EmbedBuilder::new()
+        .color(0xF8F8FF)
+        .description(description)
+        .footer(EmbedFooterBuilder::new(format!("Page {} of {page_count}", page + 1)))
+        .title("Level roles")
+        .build();
+
+    InteractionResponse {
+        data: Some(
+            InteractionResponseDataBuilder::new()
+                .components([create_page_buttons("levelrole", page, page_count)])
+                .embeds([embed])
+                .build()
+        ),
+        kind
+    }
+}
+
+impl LevelRoleCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+
+        if !has_permission(command.member.as_ref(), Permissions::MANAGE_ROLES) {
+            return create_interaction_response("You need the **Manage Roles** permission to manage level roles.", true);
+        }
+
+        let token = command.token.clone();
+        let options = LevelRoleCommand::from_interaction(command.data.into())?;
+
+        match options {
+            LevelRoleCommand::Add(LevelRoleAdd { role, kind, level, persistent }) => {
+                let kind = RoleKind::from(kind);
+                let level = level as u16;
+                let level_roles = context.database().read_level_roles(guild_id).await.unwrap_or_default();
+
+                if role.id.cast() == guild_id {
+                    return create_interaction_response("@everyone cannot be a level role.", true);
+                }
+
+                if let Some(existing) = level_roles.iter().find(|level_role| level_role.kind == kind && level_role.level == level) {
+                    let description = format!("<@&{}> is already the {} role for level {level}.", existing.role_id, kind.as_str());
+
+                    return create_interaction_response(&description, true);
+                }
+
+                if let Some(existing) = level_roles.iter().find(|level_role| level_role.kind == kind && level_role.role_id == role.id) {
+                    let description = format!("<@&{}> is already the {} role for level {}.", role.id, kind.as_str(), existing.level);
+
+                    return create_interaction_response(&description, true);
+                }
+
+                if let Err(error) = check_assignable(context, guild_id, role.id) {
+                    return create_interaction_response(&error.to_string(), true);
+                }
+
+                context.database().create_level_role(guild_id, role.id, kind, level, persistent.unwrap_or(false)).await;
+
+                let description = format!("Members will now receive <@&{}> at {} level {level}!", role.id, kind.as_str());
+
+                create_interaction_response(&description, true)
+            },
+            LevelRoleCommand::Clear(_) => {
+                context.database().delete_level_roles(guild_id).await;
+                create_interaction_response("Level roles cleared!", true)
+            },
+            LevelRoleCommand::List(_) => match context.database().read_level_roles(guild_id).await {
+                Some(level_roles) if !level_roles.is_empty() => Ok(create_list_response(&level_roles, 0, InteractionResponseType::ChannelMessageWithSource)),
+                _ => create_interaction_response("No level roles set...", true)
+            },
+            LevelRoleCommand::Remove(LevelRoleRemove { role }) => {
+                let level_roles = context.database().read_level_roles(guild_id).await.unwrap_or_default();
+
+                if level_roles.iter().any(|level_role| level_role.role_id == role.id) {
+                    context.database().delete_level_role(guild_id, role.id).await;
+
+                    let description = format!("<@&{}> is no longer a level role.", role.id);
+
+                    create_interaction_response(&description, true)
+                } else {
+                    create_interaction_response("That role is not a level role!", true)
+                }
+            },
+            LevelRoleCommand::Sync(_) => {
+                match start_sync(context, guild_id, &token).await {
+                    Some(description) => create_interaction_response(&description, true),
+                    None => create_interaction_response("A level role sync is already running for this server!", true)
+                }
+            }
+        }
+    }
+
+    pub async fn paginate(component: &MessageComponentInteraction, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = component.guild_id.unwrap();
+        let page = component.data.custom_id
+            .split(':')
+            .nth(1)
+            .and_then(|page| page.parse::<usize>().ok())
+            .unwrap_or(0);
+        let level_roles = context.database().read_level_roles(guild_id).await.unwrap_or_default();
+
+        Ok(create_list_response(&level_roles, page, InteractionResponseType::UpdateMessage))
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{
+    database::{level_role::RoleKind, setting::LevelUpDestination},
+    levels::announcement::{MAX_TEMPLATE_LENGTH, render_template, truncate_description},
+    util::{context::Context, helper::create_interaction_response, permission::{has_permission, manage_guild_permission}}
+};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_model::{
+    application::interaction::ApplicationCommand,
+    guild::Permissions,
+    http::interaction::InteractionResponse,
+    id::{Id, marker::ChannelMarker}
+};
+
+#[derive(CommandOption, CreateOption)]
+pub enum DestinationKind {
+    #[option(name = "Where the member leveled up", value = "current")]
+    Current,
+    #[option(name = "A fixed channel", value = "channel")]
+    Channel,
+    #[option(name = "Direct message", value = "direct")]
+    Direct,
+    #[option(name = "Disabled", value = "disabled")]
+    Disabled
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    default_permissions = "manage_guild_permission",
+    desc = "Manage level-up announcements",
+    dm_permission = false,
+    name = "levelup"
+)]
+pub enum LevelUpCommand {
+    #[command(name = "destination")]
+    Destination(LevelUpDestinationSet),
+    #[command(name = "preview")]
+    Preview(LevelUpPreview),
+    #[command(name = "show")]
+    Show(LevelUpShow),
+    #[command(name = "template")]
+    Template(LevelUpTemplate)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Sets where level-up announcements are sent", name = "destination")]
+pub struct LevelUpDestinationSet {
+    #[command(desc = "Where to announce level ups")]
+    destination: DestinationKind,
+    #[command(channel_types = "guild_text guild_news", desc = "The channel to use with the fixed channel destination")]
+    channel: Option<Id<ChannelMarker>>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Previews a level-up template without saving it", name = "preview")]
+pub struct LevelUpPreview {
+    #[command(desc = "The template to preview, defaulting to the current one")]
+    template: Option<String>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Shows the level-up announcement settings", name = "show")]
+pub struct LevelUpShow {}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Sets the level-up template, using {user}, {level}, {role} and {kind}", name = "template")]
+pub struct LevelUpTemplate {
+    #[command(desc = "The new template")]
+    template: String
+}
+
+fn describe_destination(destination: LevelUpDestination) -> String {
+    match destination {
+        LevelUpDestination::Current => "the channel where the member leveled up".to_string(),
+        LevelUpDestination::Channel(channel_id) => format!("<#{channel_id}>"),
+        LevelUpDestination::Direct => "a direct message".to_string(),
+        LevelUpDestination::Disabled => "nowhere, announcements are disabled".to_string()
+    }
+}
+
+impl LevelUpCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+        let member_id = command.author_id().unwrap();
+
+        if !has_permission(command.member.as_ref(), Permissions::MANAGE_GUILD) {
+            return create_interaction_response("You need the **Manage Server** permission to manage level-up announcements.", true);
+        }
+
+        let setting = match context.database().read_setting(guild_id).await {
+            Some(setting) => setting,
+            None => return create_interaction_response("Unable to find this server's settings.", true)
+        };
+        let options = LevelUpCommand::from_interaction(command.data.into())?;
+
+        match options {
+            LevelUpCommand::Destination(LevelUpDestinationSet { destination, channel }) => {
+                let destination = match (destination, channel) {
+                    (DestinationKind::Current, _) => LevelUpDestination::Current,
+                    (DestinationKind::Channel, Some(channel_id)) => LevelUpDestination::Channel(channel_id),
+                    (DestinationKind::Channel, None) => return create_interaction_response("Pick a channel to send announcements to!", true),
+                    (DestinationKind::Direct, _) => LevelUpDestination::Direct,
+                    (DestinationKind::Disabled, _) => LevelUpDestination::Disabled
+                };
+
+                context.database().update_level_up_destination(guild_id, destination).await;
+
+                let description = format!("Level-up announcements will be sent to {}.", describe_destination(destination));
+
+                create_interaction_response(&description, true)
+            },
+            LevelUpCommand::Preview(LevelUpPreview { template }) => {
+                let template = template.unwrap_or(setting.level_up_template);
+                let role_ids = context.database().read_level_roles(guild_id).await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|level_role| level_role.role_id)
+                    .take(1)
+                    .collect::<Vec<_>>();
+
+                if template.chars().count() > MAX_TEMPLATE_LENGTH {
+                    let description = format!("Templates must be at most {MAX_TEMPLATE_LENGTH} characters!");
+
+                    create_interaction_response(&description, true)
+                } else {
+                    create_interaction_response(&render_template(&template, member_id, 5, &role_ids, RoleKind::Message), true)
+                }
+            },
+            LevelUpCommand::Show(_) => {
+                let description = format!(
+                    "**Destination:** {}\n**Template:** ```{}```",
+                    describe_destination(setting.level_up_destination),
+                    setting.level_up_template
+                );
+
+                create_interaction_response(&description, true)
+            },
+            LevelUpCommand::Template(LevelUpTemplate { template }) => {
+                if template.chars().count() > MAX_TEMPLATE_LENGTH {
+                    let description = format!("Templates must be at most {MAX_TEMPLATE_LENGTH} characters!");
+
+                    create_interaction_response(&description, true)
+                } else {
+                    let description = format!("Template saved! Level ups will now look like this:\n\n{}", render_template(&template, member_id, 5, &[], RoleKind::Message));
+
+                    context.database().update_level_up_template(guild_id, template).await;
+                    create_interaction_response(&truncate_description(&description), true)
+                }
+            }
+        }
+    }
+}
@@ -1,13 +1,41 @@
 pub mod action;
+pub mod actions;
+pub mod auto_role;
 pub mod bio;
+pub mod config;
+pub mod custom_action;
 pub mod eight_ball;
+pub mod keep_roles;
 pub mod kill;
+pub mod leaderboard;
+pub mod level_role;
+pub mod level_up;
+pub mod rank;
 pub mod rate;
+pub mod season;
+pub mod shared_role;
 pub mod ship;
+pub mod xp;
+pub mod xp_event;
+pub mod xp_modifier;
 
-pub use action::{Action, get_interaction_response};
+pub use action::{Action, create_custom_action_command, get_custom_interaction_response, get_interaction_response};
+pub use actions::ActionsCommand;
+pub use auto_role::AutoRoleCommand;
 pub use bio::BioCommand;
+pub use config::ConfigCommand;
+pub use custom_action::CustomActionCommand;
 pub use eight_ball::EightBallCommand;
+pub use keep_roles::KeepRolesCommand;
 pub use kill::KillCommand;
+pub use leaderboard::LeaderboardCommand;
+pub use level_role::LevelRoleCommand;
+pub use level_up::LevelUpCommand;
+pub use rank::RankCommand;
 pub use rate::RateCommand;
-pub use ship::ShipCommand;
\ No newline at end of file
+pub use season::SeasonCommand;
+pub use shared_role::SharedRoleCommand;
+pub use ship::ShipCommand;
+pub use xp::XpCommand;
+pub use xp_event::XpEventCommand;
+pub use xp_modifier::XpModifierCommand;
\ No newline at end of file
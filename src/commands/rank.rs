@@ -0,0 +1,116 @@
+use crate::{
+    database::level_role::RoleKind,
+    levels::{LevelProgress, RankCard},
+    util::{canvas::Image, context::Context, helper::create_interaction_response}
+};
+use hyper::{body::to_bytes, Uri};
+use std::{sync::Arc, time::Duration};
+use tokio::time::timeout;
+use twilight_interactions::command::{CommandModel, CreateCommand, ResolvedUser};
+use twilight_model::{
+    application::interaction::ApplicationCommand,
+    http::{attachment::Attachment, interaction::{InteractionResponse, InteractionResponseType}},
+    user::User
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+const AVATAR_TIMEOUT: Duration = Duration::from_secs(1);
+const MAX_CACHED_AVATARS: usize = 500;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    desc = "Shows a rank card",
+    dm_permission = false,
+    name = "rank"
+)]
+pub struct RankCommand {
+    #[command(desc = "Whose rank to show")]
+    user: Option<ResolvedUser>
+}
+
+async fn read_avatar(context: &Arc<Context>, user: &User) -> Option<Image> {
+    let hash = user.avatar?;
+
+    if let Some(cached) = context.avatars().get(&user.id) {
+        if cached.0 == hash {
+            return Image::decode_png(&cached.1);
+        }
+    }
+
+    let uri = format!("https://cdn.discordapp.com/avatars/{}/{hash}.png?size=128", user.id).parse::<Uri>().ok()?;
+    // The card is rendered within the interaction's three second window, so a slow CDN falls back to the initial.
+    let bytes = timeout(AVATAR_TIMEOUT, async {
+        let response = context.hyper().get(uri).await.ok()?;
+
+        if !response.status().is_success() {
+            return None;
+        }
+
+        to_bytes(response.into_body()).await.ok()
+    }).await.ok()??.to_vec();
+    let avatar = Image::decode_png(&bytes);
+
+    if context.avatars().len() >= MAX_CACHED_AVATARS {
+        let evicted = context.avatars().iter().next().map(|entry| *entry.key());
+
+        if let Some(user_id) = evicted {
+            context.avatars().remove(&user_id);
+        }
+    }
+
+    context.avatars().insert(user.id, (hash, bytes));
+
+    avatar
+}
+
+fn describe_next_level(label: &str, progress: &LevelProgress) -> String {
+    match progress.xp_to_next_level() {
+        0 => format!("Max {label} level reached"),
+        xp => format!("**{xp} XP** to {label} level {}", progress.level + 1)
+    }
+}
+
+impl RankCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+        let author = command.member.as_ref().and_then(|member| member.user.clone()).unwrap();
+        let RankCommand { user } = RankCommand::from_interaction(command.data.into())?;
+        let user = user.map_or(author, |user| user.resolved);
+
+        if user.bot {
+            return create_interaction_response("Bots do not earn XP!", true);
+        }
+
+        let setting = match context.database().read_setting(guild_id).await {
+            Some(setting) => setting,
+            None => return create_interaction_response("Unable to find this server's settings.", true)
+        };
+        let (message_xp, voice_xp) = match context.database().read_xp(guild_id, user.id).await {
+            Some((message_xp, _, voice_xp)) => (message_xp, voice_xp),
+            None => (0, 0)
+        };
+        let card = RankCard {
+            avatar: read_avatar(context, &user).await,
+            username: user.name.clone(),
+            color: setting.rank_color,
+            message: setting.level_curve.progress(message_xp),
+            message_rank: context.database().read_rank(guild_id, user.id, RoleKind::Message).await,
+            voice: setting.level_curve.progress(voice_xp),
+            voice_rank: context.database().read_rank(guild_id, user.id, RoleKind::Voice).await
+        };
+        let content = format!("{} · {}", describe_next_level("message", &card.message), describe_next_level("voice", &card.voice));
+        let png = tokio::task::spawn_blocking(move || card.render()).await?;
+
+        Ok(
+            InteractionResponse {
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .attachments([Attachment::from_bytes("rank.png".into(), png, 0)])
+                        .content(content)
+                        .build()
+                ),
+                kind: InteractionResponseType::ChannelMessageWithSource
+            }
+        )
+    }
+}
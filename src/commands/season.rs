@@ -0,0 +1,152 @@
+use crate::{
+    levels::roles::sync_season_roles,
+    util::{
+        context::Context,
+        helper::{create_interaction_response, read_guild_members},
+        permission::{has_permission, manage_guild_permission}
+    }
+};
+use std::{collections::HashMap, sync::Arc};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::interaction::ApplicationCommand,
+    guild::Permissions,
+    http::interaction::InteractionResponse,
+    id::{Id, marker::GuildMarker}
+};
+
+const HISTORY_SIZE: i64 = 10;
+const MAX_NAME_LENGTH: usize = 100;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    default_permissions = "manage_guild_permission",
+    desc = "Manage XP seasons",
+    dm_permission = false,
+    name = "season"
+)]
+pub enum SeasonCommand {
+    #[command(name = "end")]
+    End(SeasonEnd),
+    #[command(name = "history")]
+    History(SeasonHistory)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Archives the current standings and resets everyone's XP", name = "end")]
+pub struct SeasonEnd {
+    #[command(desc = "Set to true to confirm, this cannot be undone")]
+    confirm: bool,
+    #[command(desc = "The name to archive the season under")]
+    name: Option<String>,
+    #[command(desc = "The percentage of XP members keep into the next season", max_value = 100, min_value = 0)]
+    keep_percent: Option<i64>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Shows the winners of past seasons", name = "history")]
+pub struct SeasonHistory {}
+
+/// Moves every member holding a level role onto the roles their reset XP earns, returning how many updates failed.
+async fn strip_level_roles(context: &Arc<Context>, guild_id: Id<GuildMarker>) -> Result<usize, anyhow::Error> {
+    let setting = match context.database().read_setting(guild_id).await {
+        Some(setting) => setting,
+        None => return Ok(0)
+    };
+    let level_roles = context.database().read_level_roles(guild_id).await.unwrap_or_default();
+
+    if level_roles.is_empty() {
+        return Ok(0);
+    }
+
+    let xp = context.database().read_members(guild_id).await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|member| (member.member_id, (member.message_xp, member.voice_xp)))
+        .collect::<HashMap<_, _>>();
+    let mut failures = 0;
+
+    for member in read_guild_members(context, guild_id).await? {
+        if member.user.bot || !level_roles.iter().any(|level_role| member.roles.contains(&level_role.role_id)) {
+            continue;
+        }
+
+        let (message_xp, voice_xp) = xp.get(&member.user.id).copied().unwrap_or((0, 0));
+        let changes = sync_season_roles(context, &setting, &level_roles, member.user.id, &member.roles, message_xp, voice_xp).await;
+
+        for error in &changes.errors {
+            tracing::warn!("Unable to strip level roles from {} in {guild_id}: {error}", member.user.id);
+        }
+
+        failures += changes.errors.len();
+    }
+
+    Ok(failures)
+}
+
+impl SeasonCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+
+        if !has_permission(command.member.as_ref(), Permissions::MANAGE_GUILD) {
+            return create_interaction_response("You need the **Manage Server** permission to manage seasons.", true);
+        }
+
+        let options = SeasonCommand::from_interaction(command.data.into())?;
+
+        match options {
+            SeasonCommand::End(SeasonEnd { confirm, name, keep_percent }) => {
+                if !confirm {
+                    return create_interaction_response("Nothing was reset. Set `confirm` to true to end the season.", true);
+                }
+
+                let name = match name {
+                    Some(name) if name.chars().count() > MAX_NAME_LENGTH => {
+                        return create_interaction_response(&format!("Season names must be at most {MAX_NAME_LENGTH} characters!"), true);
+                    },
+                    Some(name) => name,
+                    None => format!("Season {}", context.database().read_season_count(guild_id).await + 1)
+                };
+                let keep_percent = keep_percent.unwrap_or(0) as u8;
+                let (_, archived) = context.database().end_season(guild_id, name.clone(), keep_percent).await;
+                let mut description = format!("**{name}** has ended! Archived {archived} members and kept {keep_percent}% of their XP.");
+
+                match strip_level_roles(context, guild_id).await {
+                    Ok(0) => {},
+                    Ok(failures) => description.push_str(&format!("\n\n:warning: {failures} level role update(s) failed, check my permissions and role position.")),
+                    Err(error) => {
+                        tracing::warn!("Unable to list members of {guild_id} for a season reset: {error}");
+                        description.push_str("\n\n:warning: Unable to list the server's members, so level roles were left as they were.");
+                    }
+                }
+
+                create_interaction_response(&description, true)
+            },
+            SeasonCommand::History(_) => {
+                let seasons = context.database().read_seasons(guild_id, HISTORY_SIZE).await;
+
+                if seasons.is_empty() {
+                    return create_interaction_response("No season has ended yet!", true);
+                }
+
+                let describe_winner = |winner: Option<(Id<_>, u64)>| match winner {
+                    Some((member_id, xp)) => format!("<@{member_id}> ({xp} XP)"),
+                    None => "Nobody".to_string()
+                };
+                let description = seasons
+                    .into_iter()
+                    .map(|season| format!(
+                        "**{}** ended <t:{}:D>\nMessage: {} • Voice: {}",
+                        season.name,
+                        season.ended_at.timestamp(),
+                        describe_winner(season.message_winner),
+                        describe_winner(season.voice_winner)
+                    ))
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+
+                create_interaction_response(&description, true)
+            }
+        }
+    }
+}
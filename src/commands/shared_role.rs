@@ -0,0 +1,485 @@
+use chrono::{DateTime, Duration, Utc};
+use crate::{
+    database::SharedRole,
+    util::{context::Context, helper::{create_embed, create_interaction_response}, permission::{RoleError, has_permission}}
+};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CreateCommand, ResolvedUser};
+use twilight_model::{
+    application::{
+        component::{action_row::ActionRow, button::{Button, ButtonStyle}, Component},
+        interaction::{ApplicationCommand, message_component::MessageComponentInteraction}
+    },
+    guild::{Permissions, Role},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{Id, marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker}}
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+const INVITE_HOURS: i64 = 24;
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_OWNED_ROLES: usize = 3;
+const MAX_OWNERS: usize = 25;
+const MAX_SHARED_ROLES: usize = 50;
+
+#[allow(clippy::large_enum_variant)]
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    desc = "Manage group roles shared by their owners",
+    dm_permission = false,
+    name = "sharedrole"
+)]
+pub enum SharedRoleCommand {
+    #[command(name = "create")]
+    Create(SharedRoleCreate),
+    #[command(name = "delete")]
+    Delete(SharedRoleDelete),
+    #[command(name = "invite")]
+    Invite(SharedRoleInvite),
+    #[command(name = "list")]
+    List(SharedRoleList),
+    #[command(name = "recolor")]
+    Recolor(SharedRoleRecolor),
+    #[command(name = "removeowner")]
+    RemoveOwner(SharedRoleRemoveOwner),
+    #[command(name = "rename")]
+    Rename(SharedRoleRename)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Creates a shared role owned by you, inviting your friends to co-own it", name = "create")]
+pub struct SharedRoleCreate {
+    #[command(desc = "The role name")]
+    name: String,
+    #[command(desc = "The role color as a hex code, like #FFB6C1")]
+    color: Option<String>,
+    #[command(desc = "A member to invite as co-owner")]
+    member_one: Option<ResolvedUser>,
+    #[command(desc = "A member to invite as co-owner")]
+    member_two: Option<ResolvedUser>,
+    #[command(desc = "A member to invite as co-owner")]
+    member_three: Option<ResolvedUser>,
+    #[command(desc = "A member to invite as co-owner")]
+    member_four: Option<ResolvedUser>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Deletes a shared role, requires the Manage Server permission", name = "delete")]
+pub struct SharedRoleDelete {
+    #[command(desc = "The shared role")]
+    role: Role
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Invites a member to co-own a shared role you own", name = "invite")]
+pub struct SharedRoleInvite {
+    #[command(desc = "The shared role")]
+    role: Role,
+    #[command(desc = "The member to invite")]
+    user: ResolvedUser
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Lists the shared roles", name = "list")]
+pub struct SharedRoleList {}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Changes the color of a shared role you own", name = "recolor")]
+pub struct SharedRoleRecolor {
+    #[command(desc = "The shared role")]
+    role: Role,
+    #[command(desc = "The new color as a hex code, like #FFB6C1")]
+    color: String
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Removes a co-owner, or yourself, from a shared role you own", name = "removeowner")]
+pub struct SharedRoleRemoveOwner {
+    #[command(desc = "The shared role")]
+    role: Role,
+    #[command(desc = "The member to remove")]
+    user: ResolvedUser
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Renames a shared role you own", name = "rename")]
+pub struct SharedRoleRename {
+    #[command(desc = "The shared role")]
+    role: Role,
+    #[command(desc = "The new name")]
+    name: String
+}
+
+/// Parses a six digit hex color, with or without a leading `#`.
+pub fn parse_color(color: &str) -> Option<u32> {
+    let hex = color.trim().trim_start_matches('#');
+
+    if hex.len() != 6 {
+        return None;
+    }
+
+    u32::from_str_radix(hex, 16).ok()
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    match name.trim().chars().count() {
+        0 => Err("Role names cannot be empty!".to_string()),
+        length if length > MAX_NAME_LENGTH => Err(format!("Role names must be at most {MAX_NAME_LENGTH} characters!")),
+        _ => Ok(())
+    }
+}
+
+async fn read_owned_role(context: &Arc<Context>, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>, member_id: Id<UserMarker>) -> Result<SharedRole, String> {
+    match context.database().read_shared_role(guild_id, role_id).await {
+        Some(shared_role) if shared_role.owner_ids.contains(&member_id) => Ok(shared_role),
+        Some(_) => Err(format!("You do not own <@&{role_id}>!")),
+        None => Err(format!("<@&{role_id}> is not a shared role!"))
+    }
+}
+
+fn create_invite_components(role_id: Id<RoleMarker>, invitee_id: Id<UserMarker>) -> [Component; 1] {
+    let button = |action: &str, label: &str, style: ButtonStyle| Component::Button(Button {
+        custom_id: Some(format!("sharedrole:{action}:{role_id}:{invitee_id}")),
+        disabled: false,
+        emoji: None,
+        label: Some(label.into()),
+        style,
+        url: None
+    });
+
+    [
+        Component::ActionRow(ActionRow {
+            components: vec![button("accept", "Accept", ButtonStyle::Success), button("decline", "Decline", ButtonStyle::Danger)]
+        })
+    ]
+}
+
+fn describe_invite(inviter_id: Id<UserMarker>, role_id: Id<RoleMarker>, expires_at: DateTime<Utc>) -> String {
+    format!("<@{inviter_id}> invited you to co-own <@&{role_id}>! This invitation expires <t:{}:R>.", expires_at.timestamp())
+}
+
+fn create_invite_response(inviter_id: Id<UserMarker>, invitee_id: Id<UserMarker>, role_id: Id<RoleMarker>, expires_at: DateTime<Utc>) -> InteractionResponse {
+    InteractionResponse {
+        data: Some(
+            InteractionResponseDataBuilder::new()
+                .components(create_invite_components(role_id, invitee_id))
+                .content(format!("<@{invitee_id}>"))
+                .embeds([create_embed(&describe_invite(inviter_id, role_id, expires_at))])
+                .build()
+        ),
+        kind: InteractionResponseType::ChannelMessageWithSource
+    }
+}
+
+/// Stores an invite and posts its Accept and Decline buttons in `channel_id`, for invites made outside of `/sharedrole
+/// invite` where the interaction response is already taken.
+async fn send_invite(
+    context: &Arc<Context>,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    inviter_id: Id<UserMarker>,
+    invitee_id: Id<UserMarker>,
+    role_id: Id<RoleMarker>
+) -> Result<(), anyhow::Error> {
+    let expires_at = Utc::now() + Duration::hours(INVITE_HOURS);
+
+    context.database().create_shared_role_invite(guild_id, role_id, inviter_id, invitee_id, expires_at).await;
+    context
+        .http()
+        .create_message(channel_id)
+        .content(&format!("<@{invitee_id}>"))?
+        .components(&create_invite_components(role_id, invitee_id))?
+        .embeds(&[create_embed(&describe_invite(inviter_id, role_id, expires_at))])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+fn close_invite(description: &str) -> InteractionResponse {
+    InteractionResponse {
+        data: Some(
+            InteractionResponseDataBuilder::new()
+                .components([])
+                .embeds([create_embed(description)])
+                .build()
+        ),
+        kind: InteractionResponseType::UpdateMessage
+    }
+}
+
+/// Records the new owner and grants the role, returning `false` when the member already owns the role or it is full.
+/// The owner is dropped again if Discord refuses the grant.
+pub async fn add_owner(context: &Arc<Context>, shared_role: &SharedRole, member_id: Id<UserMarker>) -> Result<bool, RoleError> {
+    let (guild_id, role_id) = (shared_role.guild_id, shared_role.role_id);
+
+    if !context.database().add_shared_role_owner(guild_id, role_id, member_id, MAX_OWNERS).await {
+        return Ok(false);
+    }
+
+    if let Err(error) = context.http().add_guild_member_role(guild_id, member_id, role_id).exec().await {
+        context.database().remove_shared_role_owner(guild_id, role_id, member_id).await;
+
+        return Err(error.into());
+    }
+
+    Ok(true)
+}
+
+async fn create(
+    context: &Arc<Context>,
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    author_id: Id<UserMarker>,
+    options: SharedRoleCreate
+) -> Result<InteractionResponse, anyhow::Error> {
+    let SharedRoleCreate { name, color, member_one, member_two, member_three, member_four } = options;
+
+    if let Err(description) = validate_name(&name) {
+        return create_interaction_response(&description, true);
+    }
+
+    let color = match color.as_deref().map(parse_color) {
+        Some(Some(color)) => color,
+        Some(None) => return create_interaction_response("Colors must be hex codes, like #FFB6C1!", true),
+        None => 0
+    };
+    let shared_roles = context.database().read_shared_roles(guild_id).await.unwrap_or_default();
+
+    if shared_roles.len() >= MAX_SHARED_ROLES {
+        return create_interaction_response(&format!("This server already has {MAX_SHARED_ROLES} shared roles!"), true);
+    }
+
+    if shared_roles.iter().filter(|shared_role| shared_role.owner_ids.contains(&author_id)).count() >= MAX_OWNED_ROLES {
+        return create_interaction_response(&format!("You can only own up to {MAX_OWNED_ROLES} shared roles!"), true);
+    }
+
+    let mut invitee_ids = Vec::new();
+
+    for user in [member_one, member_two, member_three, member_four].into_iter().flatten() {
+        if user.resolved.bot {
+            return create_interaction_response("Bots cannot own shared roles!", true);
+        }
+
+        if user.resolved.id != author_id && !invitee_ids.contains(&user.resolved.id) {
+            invitee_ids.push(user.resolved.id);
+        }
+    }
+
+    let role = match context.http().create_role(guild_id).name(name.trim()).color(color).permissions(Permissions::empty()).exec().await {
+        Ok(response) => response.model().await?,
+        Err(error) => return create_interaction_response(&RoleError::from(error).to_string(), true)
+    };
+
+    // Only the author owns the role at first, everyone else has to accept an invite.
+    context.database().create_shared_role(guild_id, role.id, vec![author_id]).await;
+
+    let mut description = format!("<@&{}> has been created!", role.id);
+
+    if let Err(error) = context.http().add_guild_member_role(guild_id, author_id, role.id).exec().await {
+        tracing::warn!("Unable to grant shared role {} to {author_id} in {guild_id}: {error}", role.id);
+        description.push_str("\n\n:warning: Unable to grant you the role.");
+    }
+
+    let mut invited = Vec::new();
+    let mut failures = 0;
+
+    for invitee_id in invitee_ids {
+        match send_invite(context, guild_id, channel_id, author_id, invitee_id, role.id).await {
+            Ok(_) => invited.push(format!("<@{invitee_id}>")),
+            Err(error) => {
+                tracing::warn!("Unable to invite {invitee_id} to shared role {} in {guild_id}: {error}", role.id);
+                failures += 1;
+            }
+        }
+    }
+
+    if !invited.is_empty() {
+        description.push_str(&format!("\n\nInvitations were sent to {}.", invited.join(", ")));
+    }
+
+    if failures > 0 {
+        description.push_str(&format!("\n\n:warning: Unable to invite {failures} member(s), try `/sharedrole invite` instead."));
+    }
+
+    create_interaction_response(&description, true)
+}
+
+impl SharedRoleCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+        let author_id = command.author_id().unwrap();
+        let channel_id = command.channel_id;
+        let is_admin = has_permission(command.member.as_ref(), Permissions::MANAGE_GUILD);
+        let options = SharedRoleCommand::from_interaction(command.data.into())?;
+
+        match options {
+            SharedRoleCommand::Create(options) => create(context, guild_id, channel_id, author_id, options).await,
+            SharedRoleCommand::Delete(SharedRoleDelete { role }) => {
+                if !is_admin {
+                    return create_interaction_response("You need the **Manage Server** permission to delete shared roles.", true);
+                }
+
+                if context.database().read_shared_role(guild_id, role.id).await.is_none() {
+                    return create_interaction_response(&format!("<@&{}> is not a shared role!", role.id), true);
+                }
+
+                if let Err(error) = context.http().delete_role(guild_id, role.id).exec().await {
+                    return create_interaction_response(&RoleError::from(error).to_string(), true);
+                }
+
+                context.database().delete_shared_role(guild_id, role.id).await;
+                create_interaction_response(&format!("**{}** has been deleted.", role.name), true)
+            },
+            SharedRoleCommand::Invite(SharedRoleInvite { role, user }) => {
+                let shared_role = match read_owned_role(context, guild_id, role.id, author_id).await {
+                    Ok(shared_role) => shared_role,
+                    Err(description) => return create_interaction_response(&description, true)
+                };
+
+                if user.resolved.bot {
+                    return create_interaction_response("Bots cannot own shared roles!", true);
+                }
+
+                if shared_role.owner_ids.contains(&user.resolved.id) {
+                    return create_interaction_response(&format!("<@{}> already owns <@&{}>!", user.resolved.id, role.id), true);
+                }
+
+                if shared_role.owner_ids.len() >= MAX_OWNERS {
+                    return create_interaction_response(&format!("Shared roles can only have up to {MAX_OWNERS} owners!"), true);
+                }
+
+                let expires_at = Utc::now() + Duration::hours(INVITE_HOURS);
+
+                context.database().delete_expired_shared_role_invites().await;
+                context.database().create_shared_role_invite(guild_id, role.id, author_id, user.resolved.id, expires_at).await;
+
+                Ok(create_invite_response(author_id, user.resolved.id, role.id, expires_at))
+            },
+            SharedRoleCommand::List(_) => {
+                let shared_roles = context.database().read_shared_roles(guild_id).await.unwrap_or_default();
+
+                if shared_roles.is_empty() {
+                    return create_interaction_response("There are no shared roles!", true);
+                }
+
+                let description = shared_roles
+                    .iter()
+                    .map(|shared_role| {
+                        let owners = shared_role.owner_ids.iter().map(|owner_id| format!("<@{owner_id}>")).collect::<Vec<_>>().join(", ");
+
+                        format!("<@&{}>: {owners}", shared_role.role_id)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                create_interaction_response(&description, true)
+            },
+            SharedRoleCommand::Recolor(SharedRoleRecolor { role, color }) => {
+                if let Err(description) = read_owned_role(context, guild_id, role.id, author_id).await {
+                    return create_interaction_response(&description, true);
+                }
+
+                let color = match parse_color(&color) {
+                    Some(color) => color,
+                    None => return create_interaction_response("Colors must be hex codes, like #FFB6C1!", true)
+                };
+
+                match context.http().update_role(guild_id, role.id).color(Some(color)).exec().await {
+                    Ok(_) => create_interaction_response(&format!("<@&{}> has been recolored.", role.id), true),
+                    Err(error) => create_interaction_response(&RoleError::from(error).to_string(), true)
+                }
+            },
+            SharedRoleCommand::RemoveOwner(SharedRoleRemoveOwner { role, user }) => {
+                if let Err(description) = read_owned_role(context, guild_id, role.id, author_id).await {
+                    return create_interaction_response(&description, true);
+                }
+
+                let owner_ids = match context.database().remove_shared_role_owner(guild_id, role.id, user.resolved.id).await {
+                    Some(owner_ids) => owner_ids,
+                    None => return create_interaction_response(&format!("<@{}> does not own <@&{}>!", user.resolved.id, role.id), true)
+                };
+
+                // A shared role without owners has nobody left to manage it.
+                if owner_ids.is_empty() {
+                    if let Err(error) = context.http().delete_role(guild_id, role.id).exec().await {
+                        context.database().add_shared_role_owner(guild_id, role.id, user.resolved.id, MAX_OWNERS).await;
+
+                        return create_interaction_response(&RoleError::from(error).to_string(), true);
+                    }
+
+                    context.database().delete_shared_role(guild_id, role.id).await;
+
+                    return create_interaction_response(&format!("**{}** had no owners left and has been deleted.", role.name), true);
+                }
+
+                if let Err(error) = context.http().remove_guild_member_role(guild_id, user.resolved.id, role.id).exec().await {
+                    context.database().add_shared_role_owner(guild_id, role.id, user.resolved.id, MAX_OWNERS).await;
+
+                    return create_interaction_response(&RoleError::from(error).to_string(), true);
+                }
+
+                create_interaction_response(&format!("<@{}> no longer owns <@&{}>.", user.resolved.id, role.id), true)
+            },
+            SharedRoleCommand::Rename(SharedRoleRename { role, name }) => {
+                if let Err(description) = read_owned_role(context, guild_id, role.id, author_id).await {
+                    return create_interaction_response(&description, true);
+                }
+
+                if let Err(description) = validate_name(&name) {
+                    return create_interaction_response(&description, true);
+                }
+
+                match context.http().update_role(guild_id, role.id).name(Some(name.trim())).exec().await {
+                    Ok(_) => create_interaction_response(&format!("<@&{}> has been renamed.", role.id), true),
+                    Err(error) => create_interaction_response(&RoleError::from(error).to_string(), true)
+                }
+            }
+        }
+    }
+
+    /// Handles the Accept and Decline buttons of an invite, whose custom ID is
+    /// `sharedrole:{accept|decline}:{role_id}:{invitee_id}`.
+    pub async fn respond_to_invite(component: &MessageComponentInteraction, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = component.guild_id.unwrap();
+        let presser_id = component.author_id().unwrap();
+        let mut parts = component.data.custom_id.split(':').skip(1);
+        let (action, role_id, invitee_id) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(action), Some(role_id), Some(invitee_id)) => (action, role_id.parse::<Id<RoleMarker>>()?, invitee_id.parse::<Id<UserMarker>>()?),
+            _ => anyhow::bail!("Malformed shared role invite button {}", component.data.custom_id)
+        };
+
+        if presser_id != invitee_id {
+            return create_interaction_response("This invitation was not meant for you.", true);
+        }
+
+        let invite = match context.database().take_shared_role_invite(guild_id, role_id, invitee_id).await {
+            Some(invite) if invite.expires_at > Utc::now() => invite,
+            Some(_) => return Ok(close_invite(&format!("The invitation to co-own <@&{role_id}> has expired."))),
+            None => return Ok(close_invite(&format!("The invitation to co-own <@&{role_id}> is no longer valid.")))
+        };
+
+        if action != "accept" {
+            return Ok(close_invite(&format!("<@{invitee_id}> declined the invitation to co-own <@&{role_id}>.")));
+        }
+
+        let shared_role = match context.database().read_shared_role(guild_id, role_id).await {
+            Some(shared_role) => shared_role,
+            None => return Ok(close_invite(&format!("<@&{role_id}> is no longer a shared role.")))
+        };
+
+        if shared_role.owner_ids.contains(&invitee_id) {
+            return Ok(close_invite(&format!("<@{invitee_id}> already owns <@&{role_id}>!")));
+        }
+
+        if shared_role.owner_ids.len() >= MAX_OWNERS {
+            return Ok(close_invite(&format!("<@&{role_id}> already has {MAX_OWNERS} owners.")));
+        }
+
+        match add_owner(context, &shared_role, invitee_id).await {
+            Ok(true) => Ok(close_invite(&format!("<@{invitee_id}> accepted <@{}>'s invitation and now co-owns <@&{role_id}>!", invite.inviter_id))),
+            Ok(false) => Ok(close_invite(&format!("<@{invitee_id}> already owns <@&{role_id}>, or it already has {MAX_OWNERS} owners."))),
+            Err(error) => create_interaction_response(&error.to_string(), true)
+        }
+    }
+}
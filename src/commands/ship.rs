@@ -68,7 +68,7 @@ impl ShipCommand {
                         Component::ActionRow(ActionRow {
                             components: vec![
                                 Component::Button(Button {
-                                    custom_id: Some("accept".into()),
+                                    custom_id: Some("ship:accept".into()),
                                     disabled: false,
                                     emoji: None,
                                     label: Some("Accept".into()),
@@ -76,7 +76,7 @@ impl ShipCommand {
                                     url: None
                                 }),
                                 Component::Button(Button {
-                                    custom_id: Some("reject".into()),
+                                    custom_id: Some("ship:reject".into()),
                                     disabled: false,
                                     emoji: None,
                                     label: Some("Reject".into()),
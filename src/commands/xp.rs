@@ -0,0 +1,243 @@
+use crate::{
+    commands::level_role::LevelKind,
+    database::{level_role::RoleKind, xp_audit::{XpChange, XpOperation}},
+    levels::{import::{ImportFormat, MergeStrategy, parse_export, resolve_rows}, roles::resync_level_roles},
+    util::{
+        context::Context,
+        helper::{create_interaction_response, read_role_member_ids},
+        permission::{has_permission, manage_guild_permission}
+    }
+};
+use hyper::{body::to_bytes, Uri};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_model::{
+    application::interaction::ApplicationCommand,
+    channel::Attachment,
+    guild::Permissions,
+    http::interaction::InteractionResponse,
+    id::{Id, marker::{GuildMarker, RoleMarker, UserMarker}}
+};
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    default_permissions = "manage_guild_permission",
+    desc = "Manage member XP",
+    dm_permission = false,
+    name = "xp"
+)]
+pub enum XpCommand {
+    #[command(name = "add")]
+    Add(XpAdd),
+    #[command(name = "import")]
+    Import(XpImport),
+    #[command(name = "remove")]
+    Remove(XpRemove),
+    #[command(name = "reset")]
+    Reset(XpReset),
+    #[command(name = "set")]
+    Set(XpSet)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Gives XP to a member or every member with a role", name = "add")]
+pub struct XpAdd {
+    #[command(desc = "Which XP to change")]
+    kind: LevelKind,
+    #[command(desc = "How much XP to give", max_value = 1000000000, min_value = 1)]
+    amount: i64,
+    #[command(desc = "The member to change")]
+    user: Option<Id<UserMarker>>,
+    #[command(desc = "The role whose members to change")]
+    role: Option<Id<RoleMarker>>
+}
+
+#[derive(CommandOption, CreateOption)]
+pub enum ImportStrategy {
+    #[option(name = "Overwrite current XP", value = "overwrite")]
+    Overwrite,
+    #[option(name = "Add to current XP", value = "add")]
+    Add,
+    #[option(name = "Keep whichever is higher", value = "max")]
+    Max
+}
+
+impl From<ImportStrategy> for MergeStrategy {
+    fn from(strategy: ImportStrategy) -> Self {
+        match strategy {
+            ImportStrategy::Overwrite => MergeStrategy::Overwrite,
+            ImportStrategy::Add => MergeStrategy::Add,
+            ImportStrategy::Max => MergeStrategy::Max
+        }
+    }
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Imports message XP from another leveling bot's JSON or CSV export", name = "import")]
+pub struct XpImport {
+    #[command(desc = "A MEE6 leaderboard JSON or a user_id,xp,level CSV")]
+    file: Attachment,
+    #[command(desc = "How imported XP combines with current XP")]
+    strategy: ImportStrategy,
+    #[command(desc = "Only summarize what would change, defaulting to true")]
+    dry_run: Option<bool>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Takes XP from a member or every member with a role", name = "remove")]
+pub struct XpRemove {
+    #[command(desc = "Which XP to change")]
+    kind: LevelKind,
+    #[command(desc = "How much XP to take", max_value = 1000000000, min_value = 1)]
+    amount: i64,
+    #[command(desc = "The member to change")]
+    user: Option<Id<UserMarker>>,
+    #[command(desc = "The role whose members to change")]
+    role: Option<Id<RoleMarker>>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Resets the XP of a member or every member with a role", name = "reset")]
+pub struct XpReset {
+    #[command(desc = "Which XP to reset")]
+    kind: LevelKind,
+    #[command(desc = "The member to reset")]
+    user: Option<Id<UserMarker>>,
+    #[command(desc = "The role whose members to reset")]
+    role: Option<Id<RoleMarker>>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Sets the XP of a member or every member with a role", name = "set")]
+pub struct XpSet {
+    #[command(desc = "Which XP to change")]
+    kind: LevelKind,
+    #[command(desc = "The new XP", min_value = 0)]
+    amount: i64,
+    #[command(desc = "The member to change")]
+    user: Option<Id<UserMarker>>,
+    #[command(desc = "The role whose members to change")]
+    role: Option<Id<RoleMarker>>
+}
+
+fn describe_changes(changes: &[XpChange], kind: RoleKind, target: &str, failures: usize) -> String {
+    let mut description = match changes {
+        [change] => format!("{} XP of {target} went from {} to {}.", kind.as_str(), change.old_xp, change.new_xp),
+        changes => format!("Updated the {} XP of {} members with {target}.", kind.as_str(), changes.len())
+    };
+
+    if failures > 0 {
+        description.push_str(&format!("\n\n:warning: {failures} level role update(s) failed, check my permissions and role position."));
+    }
+
+    description
+}
+
+/// Discord caps regular uploads at 8 MiB, which fits well over the row limit.
+const MAX_IMPORT_SIZE: u64 = 8 * 1024 * 1024;
+
+async fn download_attachment(context: &Arc<Context>, attachment: &Attachment) -> Result<String, anyhow::Error> {
+    let response = context.hyper().get(attachment.url.parse::<Uri>()?).await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Discord returned {} for the attachment", response.status());
+    }
+
+    Ok(String::from_utf8(to_bytes(response.into_body()).await?.to_vec())?)
+}
+
+async fn import(context: &Arc<Context>, guild_id: Id<GuildMarker>, moderator_id: Id<UserMarker>, options: XpImport) -> Result<InteractionResponse, anyhow::Error> {
+    let XpImport { file, strategy, dry_run } = options;
+    let dry_run = dry_run.unwrap_or(true);
+
+    if file.size > MAX_IMPORT_SIZE {
+        return create_interaction_response("That file is too large to import!", true);
+    }
+
+    let setting = match context.database().read_setting(guild_id).await {
+        Some(setting) => setting,
+        None => return create_interaction_response("Unable to find this server's settings.", true)
+    };
+    let contents = match download_attachment(context, &file).await {
+        Ok(contents) => contents,
+        Err(error) => {
+            tracing::warn!("Unable to download XP import for {guild_id}: {error}");
+            return create_interaction_response("Unable to download that file, make sure it is a text export.", true);
+        }
+    };
+    let rows = match ImportFormat::detect(&file.filename, &contents).and_then(|format| parse_export(format, &contents)) {
+        Ok(rows) => rows,
+        Err(error) => return create_interaction_response(&error.to_string(), true)
+    };
+    let imported = resolve_rows(&rows, &setting.level_curve);
+    let changes = context.database().import_message_xp(guild_id, moderator_id, &imported, strategy.into(), dry_run).await;
+    let new_members = changes.iter().filter(|change| change.old_xp == 0).count();
+    let total_before = changes.iter().map(|change| change.old_xp).sum::<u64>();
+    let total_after = changes.iter().map(|change| change.new_xp).sum::<u64>();
+    let mut description = format!(
+        "Read {} rows covering {} members.\n**{}** members {} change, **{new_members}** of them starting from 0 XP.\nTheir message XP {} from {total_before} to {total_after}.",
+        rows.len(),
+        imported.len(),
+        changes.len(),
+        if dry_run { "would" } else { "had their XP" },
+        if dry_run { "would go" } else { "went" }
+    );
+
+    if dry_run {
+        description.insert_str(0, "**Dry run, nothing was saved.**\n");
+        description.push_str("\n\nRun this again with `dry_run` set to false to import.");
+    } else if !changes.is_empty() {
+        description.push_str("\n\nRun `/levelrole sync` to hand out the level roles that go with the imported XP.");
+    }
+
+    create_interaction_response(&description, true)
+}
+
+impl XpCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+        let moderator_id = command.author_id().unwrap();
+
+        if !has_permission(command.member.as_ref(), Permissions::MANAGE_GUILD) {
+            return create_interaction_response("You need the **Manage Server** permission to manage XP.", true);
+        }
+
+        let options = XpCommand::from_interaction(command.data.into())?;
+        let (kind, operation, user, role) = match options {
+            XpCommand::Import(options) => return import(context, guild_id, moderator_id, options).await,
+            XpCommand::Add(XpAdd { kind, amount, user, role }) => (kind, XpOperation::Add(amount as u64), user, role),
+            XpCommand::Remove(XpRemove { kind, amount, user, role }) => (kind, XpOperation::Remove(amount as u64), user, role),
+            XpCommand::Reset(XpReset { kind, user, role }) => (kind, XpOperation::Reset, user, role),
+            XpCommand::Set(XpSet { kind, amount, user, role }) => (kind, XpOperation::Set(amount as u64), user, role)
+        };
+        let kind = RoleKind::from(kind);
+        let (member_ids, target) = match (user, role) {
+            (Some(user_id), None) => (vec![user_id], format!("<@{user_id}>")),
+            (None, Some(role_id)) => (read_role_member_ids(context, guild_id, role_id).await?, format!("<@&{role_id}>")),
+            _ => return create_interaction_response("Pick either a user or a role!", true)
+        };
+
+        if member_ids.is_empty() {
+            return create_interaction_response("Nobody has that role!", true);
+        }
+
+        let setting = match context.database().read_setting(guild_id).await {
+            Some(setting) => setting,
+            None => return create_interaction_response("Unable to find this server's settings.", true)
+        };
+        let changes = context.database().update_xp_with_audit(guild_id, moderator_id, &member_ids, kind, operation).await;
+        let mut failures = 0;
+
+        for change in &changes {
+            let role_changes = resync_level_roles(context, &setting, change.member_id).await;
+
+            for error in &role_changes.errors {
+                tracing::warn!("Unable to sync level roles for {} in {guild_id}: {error}", change.member_id);
+            }
+
+            failures += role_changes.errors.len();
+        }
+
+        create_interaction_response(&describe_changes(&changes, kind, &target, failures), true)
+    }
+}
@@ -0,0 +1,121 @@
+use chrono::{Duration, Utc};
+use crate::{
+    commands::xp_modifier::format_multiplier,
+    levels::modifier::MAX_MULTIPLIER,
+    util::{context::Context, helper::create_interaction_response, permission::{has_permission, manage_guild_permission}}
+};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::interaction::ApplicationCommand,
+    guild::Permissions,
+    http::interaction::InteractionResponse
+};
+
+const MAX_EVENTS: usize = 10;
+const MAX_NAME_LENGTH: usize = 100;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    default_permissions = "manage_guild_permission",
+    desc = "Manage time-boxed XP events",
+    dm_permission = false,
+    name = "xpevent"
+)]
+pub enum XpEventCommand {
+    #[command(name = "create")]
+    Create(XpEventCreate),
+    #[command(name = "delete")]
+    Delete(XpEventDelete),
+    #[command(name = "list")]
+    List(XpEventList)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Schedules an XP event, such as a double XP weekend", name = "create")]
+pub struct XpEventCreate {
+    #[command(desc = "The event name")]
+    name: String,
+    #[command(desc = "The multiplier as a percentage, where 200 is double XP", max_value = 500, min_value = 101)]
+    percent: i64,
+    #[command(desc = "How many hours the event lasts", max_value = 336, min_value = 1)]
+    hours: i64,
+    #[command(desc = "How many hours from now the event starts, defaulting to right away", max_value = 8760, min_value = 0)]
+    starts_in: Option<i64>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Cancels an XP event", name = "delete")]
+pub struct XpEventDelete {
+    #[command(desc = "The event ID shown by /xpevent list", min_value = 1)]
+    id: i64
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Lists the running and upcoming XP events", name = "list")]
+pub struct XpEventList {}
+
+impl XpEventCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+
+        if !has_permission(command.member.as_ref(), Permissions::MANAGE_GUILD) {
+            return create_interaction_response("You need the **Manage Server** permission to manage XP events.", true);
+        }
+
+        let options = XpEventCommand::from_interaction(command.data.into())?;
+
+        match options {
+            XpEventCommand::Create(XpEventCreate { name, percent, hours, starts_in }) => {
+                if name.chars().count() > MAX_NAME_LENGTH {
+                    return create_interaction_response(&format!("Event names must be at most {MAX_NAME_LENGTH} characters!"), true);
+                }
+
+                if context.database().read_xp_events(guild_id).await.len() >= MAX_EVENTS {
+                    return create_interaction_response(&format!("You can only schedule up to {MAX_EVENTS} XP events!"), true);
+                }
+
+                let multiplier = (percent as u32).min(MAX_MULTIPLIER);
+                let starts_at = Utc::now() + Duration::hours(starts_in.unwrap_or(0));
+                let ends_at = starts_at + Duration::hours(hours);
+                let id = context.database().create_xp_event(guild_id, name.clone(), multiplier as u16, starts_at, ends_at).await;
+                let description = format!(
+                    "**{name}** (#{id}) gives {} from <t:{}:f> until <t:{}:f>.",
+                    format_multiplier(multiplier),
+                    starts_at.timestamp(),
+                    ends_at.timestamp()
+                );
+
+                create_interaction_response(&description, true)
+            },
+            XpEventCommand::Delete(XpEventDelete { id }) => {
+                match context.database().delete_xp_event(guild_id, id).await {
+                    true => create_interaction_response(&format!("Event #{id} has been cancelled."), true),
+                    false => create_interaction_response(&format!("There is no event #{id}!"), true)
+                }
+            },
+            XpEventCommand::List(_) => {
+                let events = context.database().read_xp_events(guild_id).await;
+
+                if events.is_empty() {
+                    return create_interaction_response("There are no running or upcoming XP events!", true);
+                }
+
+                let description = events
+                    .iter()
+                    .map(|event| format!(
+                        "**#{}** {}: {} from <t:{}:f> until <t:{}:f>",
+                        event.id,
+                        event.name,
+                        format_multiplier(event.multiplier as u32),
+                        event.starts_at.timestamp(),
+                        event.ends_at.timestamp()
+                    ))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                create_interaction_response(&description, true)
+            }
+        }
+    }
+}
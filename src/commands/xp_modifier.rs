@@ -0,0 +1,129 @@
+use crate::{
+    database::xp_modifier::ModifierTarget,
+    levels::modifier::{BASE_MULTIPLIER, MAX_MULTIPLIER},
+    util::{context::Context, helper::create_interaction_response, permission::{has_permission, manage_guild_permission}}
+};
+use std::sync::Arc;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::interaction::ApplicationCommand,
+    guild::Permissions,
+    http::interaction::InteractionResponse,
+    id::{Id, marker::{ChannelMarker, RoleMarker}}
+};
+
+const MAX_MODIFIERS: usize = 50;
+
+#[derive(CommandModel, CreateCommand)]
+#[command(
+    default_permissions = "manage_guild_permission",
+    desc = "Manage XP multipliers and no-XP channels and roles",
+    dm_permission = false,
+    name = "xpmodifier"
+)]
+pub enum XpModifierCommand {
+    #[command(name = "channel")]
+    Channel(XpModifierChannel),
+    #[command(name = "list")]
+    List(XpModifierList),
+    #[command(name = "remove")]
+    Remove(XpModifierRemove),
+    #[command(name = "role")]
+    Role(XpModifierRole)
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Sets the XP multiplier of a channel, category or thread", name = "channel")]
+pub struct XpModifierChannel {
+    #[command(desc = "The channel to modify")]
+    channel: Id<ChannelMarker>,
+    #[command(desc = "The multiplier as a percentage, where 150 is 1.5x and 0 disables XP", max_value = 500, min_value = 0)]
+    percent: i64
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Lists the XP modifiers", name = "list")]
+pub struct XpModifierList {}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Removes the XP modifier of a channel or role", name = "remove")]
+pub struct XpModifierRemove {
+    #[command(desc = "The channel to reset")]
+    channel: Option<Id<ChannelMarker>>,
+    #[command(desc = "The role to reset")]
+    role: Option<Id<RoleMarker>>
+}
+
+#[derive(CommandModel, CreateCommand)]
+#[command(desc = "Sets the XP multiplier of members with a role", name = "role")]
+pub struct XpModifierRole {
+    #[command(desc = "The role to modify")]
+    role: Id<RoleMarker>,
+    #[command(desc = "The multiplier as a percentage, where 150 is 1.5x and 0 disables XP", max_value = 500, min_value = 0)]
+    percent: i64
+}
+
+pub fn format_multiplier(multiplier: u32) -> String {
+    match multiplier {
+        0 => "no XP".to_string(),
+        multiplier => format!("{}x", multiplier as f64 / BASE_MULTIPLIER as f64)
+    }
+}
+
+impl XpModifierCommand {
+    pub async fn run(command: ApplicationCommand, context: &Arc<Context>) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = command.guild_id.unwrap();
+
+        if !has_permission(command.member.as_ref(), Permissions::MANAGE_GUILD) {
+            return create_interaction_response("You need the **Manage Server** permission to manage XP modifiers.", true);
+        }
+
+        let modifiers = context.database().read_xp_modifiers(guild_id).await;
+        let options = XpModifierCommand::from_interaction(command.data.into())?;
+        let (target_id, target, mention, percent) = match options {
+            XpModifierCommand::Channel(XpModifierChannel { channel, percent }) => (channel.cast(), ModifierTarget::Channel, format!("<#{channel}>"), percent),
+            XpModifierCommand::Role(XpModifierRole { role, percent }) => (role.cast(), ModifierTarget::Role, format!("<@&{role}>"), percent),
+            XpModifierCommand::List(_) => {
+                if modifiers.is_empty() {
+                    return create_interaction_response("There are no XP modifiers!", true);
+                }
+
+                let description = modifiers
+                    .iter()
+                    .map(|modifier| {
+                        let mention = match modifier.target {
+                            ModifierTarget::Channel => format!("<#{}>", modifier.target_id),
+                            ModifierTarget::Role => format!("<@&{}>", modifier.target_id)
+                        };
+
+                        format!("{mention}: {}", format_multiplier(modifier.multiplier as u32))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                return create_interaction_response(&description, true);
+            },
+            XpModifierCommand::Remove(XpModifierRemove { channel, role }) => {
+                let (target_id, mention) = match (channel, role) {
+                    (Some(channel_id), None) => (channel_id.cast(), format!("<#{channel_id}>")),
+                    (None, Some(role_id)) => (role_id.cast(), format!("<@&{role_id}>")),
+                    _ => return create_interaction_response("Pick either a channel or a role!", true)
+                };
+
+                return match context.database().delete_xp_modifier(guild_id, target_id).await {
+                    true => create_interaction_response(&format!("{mention} earns XP normally again."), true),
+                    false => create_interaction_response(&format!("{mention} has no XP modifier!"), true)
+                };
+            }
+        };
+
+        if modifiers.len() >= MAX_MODIFIERS && !modifiers.iter().any(|modifier| modifier.target_id == target_id) {
+            return create_interaction_response(&format!("You can only have up to {MAX_MODIFIERS} XP modifiers!"), true);
+        }
+
+        let multiplier = (percent as u32).min(MAX_MULTIPLIER);
+
+        context.database().update_xp_modifier(guild_id, target_id, target, multiplier as u16).await;
+        create_interaction_response(&format!("{mention} now earns {}.", format_multiplier(multiplier)), true)
+    }
+}
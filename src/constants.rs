@@ -10,5 +10,6 @@ lazy_static! {
     pub static ref DEVELOPMENT_GUILD_ID: Id<GuildMarker> = Id::new(env::var("DEVELOPMENT_GUILD_ID").unwrap().parse::<u64>().unwrap());
     pub static ref ENVIRONMENT: String = env::var("ENVIRONMENT").unwrap();
     pub static ref EVENT_TYPES: EventTypeFlags = EventTypeFlags::SHARD_PAYLOAD;
-    pub static ref INTENTS: Intents = Intents::GUILDS | Intents::GUILD_MEMBERS | Intents::GUILD_MESSAGES | Intents::MESSAGE_CONTENT;
+    pub static ref GIF_LIST_PATH: Option<String> = env::var("GIF_LIST_PATH").ok().filter(|path| !path.is_empty());
+    pub static ref INTENTS: Intents = Intents::GUILDS | Intents::GUILD_MEMBERS | Intents::GUILD_MESSAGES | Intents::GUILD_VOICE_STATES | Intents::MESSAGE_CONTENT;
 }
\ No newline at end of file
@@ -1,13 +1,26 @@
 use crate::database::Database;
 use std::fmt;
 use tokio_postgres::Row;
-use twilight_model::id::{Id, marker::{GuildMarker, UserMarker}};
+use twilight_model::id::{Id, marker::{ChannelMarker, GuildMarker, UserMarker}};
 
 pub struct CountedAction {
-    pub cuddle: u16,
-    pub handhold: u16,
-    pub hug: u16,
-    pub kiss: u16,
+    pub cuddle: u64,
+    pub handhold: u64,
+    pub hug: u64,
+    pub kiss: u64,
+}
+
+/// How often a member did an action and had it done to them.
+pub struct ActionTally {
+    pub action: String,
+    pub given: u64,
+    pub received: u64
+}
+
+pub struct ActionPartner {
+    pub partner_id: Id<UserMarker>,
+    pub given: u64,
+    pub received: u64
 }
 
 impl fmt::Display for CountedAction {
@@ -17,10 +30,10 @@ impl fmt::Display for CountedAction {
         let hug_text = if self.hug == 1 { ":hugging: 1 hug".to_string() } else { format!(":hugging: {} hugs", self.hug) };
         let kiss_text = if self.kiss == 1 { ":kissing_heart: 1 kiss".to_string() } else { format!(":kissing_heart: {} kisses", self.kiss) };
         let counts = if self.cuddle + self.handhold + self.hug + self.kiss > 0 {
-            let parts = vec![(self.cuddle, cuddle_text), (self.handhold, handhold_text), (self.hug, hug_text), (self.kiss, kiss_text)];
+            let parts = [(self.cuddle, cuddle_text), (self.handhold, handhold_text), (self.hug, hug_text), (self.kiss, kiss_text)];
 
             parts.iter().filter_map(|(value, text)| {
-                if *value > 0 { Some(format!("{text}")) } else { None }
+                if *value > 0 { Some(text.clone()) } else { None }
             }).collect::<Vec<String>>().join("\n")
         } else { ":pensive: No counted actions...".to_string() };
 
@@ -28,13 +41,33 @@ impl fmt::Display for CountedAction {
     }
 }
 
+impl From<Row> for ActionTally {
+    fn from(row: Row) -> Self {
+        Self {
+            action: row.get(0),
+            given: row.get::<_, i64>(1) as u64,
+            received: row.get::<_, i64>(2) as u64
+        }
+    }
+}
+
+impl From<Row> for ActionPartner {
+    fn from(row: Row) -> Self {
+        Self {
+            partner_id: Id::new(row.get::<_, i64>(0) as u64),
+            given: row.get::<_, i64>(1) as u64,
+            received: row.get::<_, i64>(2) as u64
+        }
+    }
+}
+
 impl From<Row> for CountedAction {
     fn from(row: Row) -> Self {
         Self {
-            cuddle: row.get::<_, i16>(0) as u16,
-            handhold: row.get::<_, i16>(1) as u16,
-            hug: row.get::<_, i16>(2) as u16,
-            kiss: row.get::<_, i16>(3) as u16,
+            cuddle: row.get::<_, i64>(0) as u64,
+            handhold: row.get::<_, i64>(1) as u64,
+            hug: row.get::<_, i64>(2) as u64,
+            kiss: row.get::<_, i64>(3) as u64,
         }
     }
 }
@@ -42,16 +75,57 @@ impl From<Row> for CountedAction {
 
 
 impl Database {
+    /// Logs an action and bumps its counter in `action_count`, returning how often the member did it to the recipient.
+    /// Errors are returned rather than unwrapped so the caller never shows a count that was not stored.
+    pub async fn create_action_event(
+        &self,
+        guild_id: Id<GuildMarker>,
+        channel_id: Id<ChannelMarker>,
+        member_id: Id<UserMarker>,
+        recipient_id: Id<UserMarker>,
+        action: &str
+    ) -> Result<u64, tokio_postgres::Error> {
+        let client = self.get_object().await;
+        let query = "
+            WITH event AS (
+                INSERT INTO action_event(guild_id, channel_id, member_id, recipient_id, action)
+                VALUES($1, $2, $3, $4, $5)
+                RETURNING created_at
+            )
+            INSERT INTO action_count(guild_id, action, member_id, recipient_id, count, first_at, last_at)
+            SELECT $1, $5, $3, $4, 1, created_at, created_at FROM event
+            ON CONFLICT (guild_id, action, member_id, recipient_id)
+            DO UPDATE SET
+                count = action_count.count + 1,
+                first_at = COALESCE(action_count.first_at, EXCLUDED.first_at),
+                last_at = EXCLUDED.last_at
+            RETURNING count;
+        ";
+
+        let row = client.query_one(
+            query,
+            &[
+                &(guild_id.get() as i64),
+                &(channel_id.get() as i64),
+                &(member_id.get() as i64),
+                &(recipient_id.get() as i64),
+                &action
+            ]
+        ).await?;
+
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
     pub async fn read_action_counts(&self, guild_id: Id<GuildMarker>, id_one: Id<UserMarker>, id_two: Id<UserMarker>) -> CountedAction {
         let client = self.get_object().await;
         let query = "
             SELECT
-                SUM(cuddle)::INT2 AS cuddle,
-                SUM(handhold)::INT2 AS handhold,
-                SUM(hug)::INT2 AS hug,
-                SUM(kiss)::INT2 AS kiss
+                COALESCE(SUM(count) FILTER (WHERE action = 'cuddle'), 0)::INT8 AS cuddle,
+                COALESCE(SUM(count) FILTER (WHERE action = 'handhold'), 0)::INT8 AS handhold,
+                COALESCE(SUM(count) FILTER (WHERE action = 'hug'), 0)::INT8 AS hug,
+                COALESCE(SUM(count) FILTER (WHERE action = 'kiss'), 0)::INT8 AS kiss
             FROM
-                action
+                action_count
             WHERE
                 guild_id = $1
                 AND ((member_id = $2 AND recipient_id = $3) OR (member_id = $3 AND recipient_id = $2));
@@ -67,32 +141,89 @@ impl Database {
         ).await.unwrap().into()
     }
 
-    pub async fn read_kill_counts(&self, guild_id: Id<GuildMarker>, id_one: Id<UserMarker>, id_two: Id<UserMarker>) -> (u16, u16) {
+    /// Every member the given member exchanged actions with, most active first. Actions done to oneself are left out.
+    pub async fn read_action_partners(&self, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>, limit: i64) -> Vec<ActionPartner> {
+        let client = self.get_object().await;
+        let query = "
+            SELECT
+                partner_id,
+                SUM(given)::INT8 AS given,
+                SUM(received)::INT8 AS received
+            FROM (
+                SELECT recipient_id AS partner_id, count AS given, 0 AS received
+                FROM action_count
+                WHERE guild_id = $1 AND member_id = $2 AND recipient_id <> $2
+                UNION ALL
+                SELECT member_id AS partner_id, 0 AS given, count AS received
+                FROM action_count
+                WHERE guild_id = $1 AND recipient_id = $2 AND member_id <> $2
+            ) AS partners
+            GROUP BY
+                partner_id
+            ORDER BY
+                SUM(given) + SUM(received) DESC,
+                partner_id
+            LIMIT $3;
+        ";
+
+        client
+            .query(query, &[&(guild_id.get() as i64), &(member_id.get() as i64), &limit])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(ActionPartner::from)
+            .collect()
+    }
+
+    /// Counts each action a member gave and received, optionally only those exchanged with `partner_id`.
+    pub async fn read_action_tallies(&self, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>, partner_id: Option<Id<UserMarker>>) -> Vec<ActionTally> {
         let client = self.get_object().await;
         let query = "
-            WITH id_one AS (
-                SELECT
-                    COALESCE(SUM(kill), 0)::INT2 AS id_one
-                FROM
-                    action
-                WHERE
-                    guild_id = $1
-                    AND (member_id = $2 AND recipient_id = $3)
-            ),
-            id_two AS (
-                SELECT
-                    COALESCE(SUM(kill), 0)::INT2 AS id_two
-                FROM
-                    action
-                WHERE
-                    guild_id = $1
-                    AND (member_id = $3 AND recipient_id = $2)
+            SELECT
+                action,
+                COALESCE(SUM(count) FILTER (WHERE member_id = $2), 0)::INT8 AS given,
+                COALESCE(SUM(count) FILTER (WHERE recipient_id = $2), 0)::INT8 AS received
+            FROM
+                action_count
+            WHERE
+                guild_id = $1
+                AND (member_id = $2 OR recipient_id = $2)
+                AND ($3::INT8 IS NULL OR member_id = $3 OR recipient_id = $3)
+            GROUP BY
+                action
+            ORDER BY
+                SUM(count) DESC,
+                action;
+        ";
+
+        client
+            .query(
+                query,
+                &[
+                    &(guild_id.get() as i64),
+                    &(member_id.get() as i64),
+                    &partner_id.map(|id| id.get() as i64)
+                ]
             )
+            .await
+            .unwrap()
+            .into_iter()
+            .map(ActionTally::from)
+            .collect()
+    }
+
+    pub async fn read_kill_counts(&self, guild_id: Id<GuildMarker>, id_one: Id<UserMarker>, id_two: Id<UserMarker>) -> (u64, u64) {
+        let client = self.get_object().await;
+        let query = "
             SELECT
-                *
+                COALESCE(SUM(count) FILTER (WHERE member_id = $2), 0)::INT8 AS id_one,
+                COALESCE(SUM(count) FILTER (WHERE member_id = $3), 0)::INT8 AS id_two
             FROM
-                id_one,
-                id_two;
+                action_count
+            WHERE
+                guild_id = $1
+                AND action = 'kill'
+                AND ((member_id = $2 AND recipient_id = $3) OR (member_id = $3 AND recipient_id = $2));
         ";
         let row = client.query_one(
             query,
@@ -102,30 +233,37 @@ impl Database {
                 &(id_two.get() as i64)
             ]
         ).await.unwrap();
-        
-        (row.get::<_, i16>(0) as u16, row.get::<_, i16>(1) as u16)
+
+        (row.get::<_, i64>(0) as u64, row.get::<_, i64>(1) as u64)
     }
 
-    pub async fn upsert_action(&self, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>, recipient_id: Id<UserMarker>, action: &str) -> u16 {
+    /// The members who received an action the most from others.
+    pub async fn read_top_recipients(&self, guild_id: Id<GuildMarker>, action: &str, limit: i64) -> Vec<(Id<UserMarker>, u64)> {
         let client = self.get_object().await;
-        let query = format!("
-            INSERT INTO action(guild_id, member_id, recipient_id, {action})
-            VALUES($1, $2, $3, 1)
-            ON CONFLICT (guild_id, member_id, recipient_id)
-            DO UPDATE SET {action} = action.{action} + 1
-            RETURNING {action};
-        ");
-
-        match client.query_one(
-            &query,
-            &[
-                &(guild_id.get() as i64),
-                &(member_id.get() as i64),
-                &(recipient_id.get() as i64)
-            ]
-        ).await {
-            Ok(row) => row.get::<_, i16>(0) as u16,
-            Err(_) => 1u16,
-        }
+        let query = "
+            SELECT
+                recipient_id,
+                SUM(count)::INT8 AS received
+            FROM
+                action_count
+            WHERE
+                guild_id = $1
+                AND action = $2
+                AND member_id <> recipient_id
+            GROUP BY
+                recipient_id
+            ORDER BY
+                received DESC,
+                recipient_id
+            LIMIT $3;
+        ";
+
+        client
+            .query(query, &[&(guild_id.get() as i64), &action, &limit])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|row| (Id::new(row.get::<_, i64>(0) as u64), row.get::<_, i64>(1) as u64))
+            .collect()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,99 @@
+use crate::database::Database;
+use tokio_postgres::Row;
+use twilight_model::id::{Id, marker::GuildMarker};
+
+pub struct CustomAction {
+    pub guild_id: Id<GuildMarker>,
+    pub name: String,
+    pub phrase: String,
+    pub plural: String,
+    pub self_phrase: String,
+    pub gif_urls: Vec<String>
+}
+
+impl From<Row> for CustomAction {
+    fn from(row: Row) -> Self {
+        Self {
+            guild_id: Id::new(row.get::<_, i64>(0) as u64),
+            name: row.get(1),
+            phrase: row.get(2),
+            plural: row.get(3),
+            self_phrase: row.get(4),
+            gif_urls: row.get(5)
+        }
+    }
+}
+
+impl Database {
+    /// Returns `false` when the guild already has an action with that name.
+    pub async fn create_custom_action(&self, custom_action: &CustomAction) -> bool {
+        let client = self.get_object().await;
+        let query = "
+            INSERT INTO custom_action(guild_id, name, phrase, plural, self_phrase, gif_urls)
+            VALUES($1, $2, $3, $4, $5, $6)
+            ON CONFLICT DO NOTHING;
+        ";
+
+        client.execute(
+            query,
+            &[
+                &(custom_action.guild_id.get() as i64),
+                &custom_action.name,
+                &custom_action.phrase,
+                &custom_action.plural,
+                &custom_action.self_phrase,
+                &custom_action.gif_urls
+            ]
+        ).await.unwrap() > 0
+    }
+
+    /// Deletes the action along with its logged events and counts.
+    pub async fn delete_custom_action(&self, guild_id: Id<GuildMarker>, name: &str) -> bool {
+        let mut client = self.get_object().await;
+        let transaction = client.transaction().await.unwrap();
+        let guild_id = guild_id.get() as i64;
+        let deleted = transaction
+            .execute("DELETE FROM custom_action WHERE guild_id = $1 AND name = $2;", &[&guild_id, &name])
+            .await
+            .unwrap() > 0;
+
+        if deleted {
+            transaction.execute("DELETE FROM action_event WHERE guild_id = $1 AND action = $2;", &[&guild_id, &name]).await.unwrap();
+            transaction.execute("DELETE FROM action_count WHERE guild_id = $1 AND action = $2;", &[&guild_id, &name]).await.unwrap();
+        }
+
+        transaction.commit().await.unwrap();
+
+        deleted
+    }
+
+    pub async fn read_custom_action(&self, guild_id: Id<GuildMarker>, name: &str) -> Option<CustomAction> {
+        let client = self.get_object().await;
+        let query = "SELECT * FROM custom_action WHERE guild_id = $1 AND name = $2;";
+
+        match client.query_one(query, &[&(guild_id.get() as i64), &name]).await {
+            Ok(row) => Some(row.into()),
+            Err(_) => None
+        }
+    }
+
+    pub async fn read_custom_actions(&self, guild_id: Id<GuildMarker>) -> Vec<CustomAction> {
+        let client = self.get_object().await;
+        let query = "SELECT * FROM custom_action WHERE guild_id = $1 ORDER BY name;";
+
+        client
+            .query(query, &[&(guild_id.get() as i64)])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(CustomAction::from)
+            .collect()
+    }
+
+    pub async fn update_custom_action_gif_urls(&self, guild_id: Id<GuildMarker>, name: &str, gif_urls: &[String]) -> bool {
+        let client = self.get_object().await;
+        let query = "UPDATE custom_action SET gif_urls = $3 WHERE guild_id = $1 AND name = $2;";
+
+        client.execute(query, &[&(guild_id.get() as i64), &name, &gif_urls]).await.unwrap() > 0
+    }
+}
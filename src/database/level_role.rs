@@ -2,6 +2,7 @@ use crate::database::Database;
 use tokio_postgres::Row;
 use twilight_model::id::{Id, marker::{GuildMarker, RoleMarker}};
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum RoleKind {
     Message,
     Voice    
@@ -16,12 +17,19 @@ pub struct LevelRole {
 }
 
 impl RoleKind {
-    fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             RoleKind::Message => "message",
             RoleKind::Voice => "voice",
         }
     }
+
+    pub fn xp_column(&self) -> &'static str {
+        match self {
+            RoleKind::Message => "message_xp",
+            RoleKind::Voice => "voice_xp",
+        }
+    }
 }
 
 impl From<Row> for LevelRole {
@@ -42,7 +50,7 @@ impl From<Row> for LevelRole {
 impl Database {
     pub async fn create_level_role(&self, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>, kind: RoleKind, level: u16, is_persistent: bool) {
         let client = self.get_object().await;
-        let query = "INSERT INTO level_role(guild_id, role_id, kind, level, is_persistent) VALUES($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING;";
+        let query = "INSERT INTO level_role(guild_id, role_id, kind, level, is_persistent) VALUES($1, $2, $3::TEXT::role_kind, $4, $5) ON CONFLICT DO NOTHING;";
 
         client.query(
             query,
@@ -56,11 +64,11 @@ impl Database {
         ).await.unwrap();
     }
 
-    pub async fn delete_level_role(&self, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>) {
+    pub async fn delete_level_role(&self, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>) -> bool {
         let client = self.get_object().await;
         let query = "DELETE FROM level_role WHERE guild_id = $1 AND role_id = $2;";
 
-        client.query(query, &[&(guild_id.get() as i64), &(role_id.get() as i64)]).await.unwrap();
+        client.execute(query, &[&(guild_id.get() as i64), &(role_id.get() as i64)]).await.unwrap() > 0
     }
 
     pub async fn delete_level_roles(&self, guild_id: Id<GuildMarker>) {
@@ -72,7 +80,7 @@ impl Database {
 
     pub async fn read_level_role(&self, guild_id: Id<GuildMarker>, kind: RoleKind, level: u16) -> Option<LevelRole> {
         let client = self.get_object().await;
-        let query = "SELECT * FROM level_role WHERE guild_id = $1 AND kind = $2 AND level = $3;";
+        let query = "SELECT guild_id, role_id, kind::TEXT, level, is_persistent FROM level_role WHERE guild_id = $1 AND kind = $2::TEXT::role_kind AND level = $3;";
 
         match client.query_one(query, &[&(guild_id.get() as i64), &(kind.as_str()), &(level as i16)]).await {
             Ok(row) => Some(row.into()),
@@ -82,7 +90,7 @@ impl Database {
 
     pub async fn read_level_roles(&self, guild_id: Id<GuildMarker>) -> Option<Vec<LevelRole>> {
         let client = self.get_object().await;
-        let query = "SELECT * FROM level_role WHERE guild_id = $1;";
+        let query = "SELECT guild_id, role_id, kind::TEXT, level, is_persistent FROM level_role WHERE guild_id = $1 ORDER BY kind, level;";
 
         match client.query(query, &[&(guild_id.get() as i64)]).await {
             Ok(rows) => {
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use crate::database::Database;
+use tokio_postgres::Row;
+use twilight_model::id::{Id, marker::{GuildMarker, UserMarker}};
+
+pub struct LevelRoleSync {
+    pub guild_id: Id<GuildMarker>,
+    pub interaction_token: String,
+    pub cursor: Option<Id<UserMarker>>,
+    pub processed: u64,
+    pub failures: u64,
+    pub started_at: DateTime<Utc>
+}
+
+impl From<Row> for LevelRoleSync {
+    fn from(row: Row) -> Self {
+        Self {
+            guild_id: Id::new(row.get::<_, i64>(0) as u64),
+            interaction_token: row.get(1),
+            cursor: row.get::<_, Option<i64>>(2).map(|id| Id::new(id as u64)),
+            processed: row.get::<_, i64>(3) as u64,
+            failures: row.get::<_, i64>(4) as u64,
+            started_at: row.get::<_, DateTime<Utc>>(5)
+        }
+    }
+}
+
+impl Database {
+    /// Registers a new sync job, returning `None` if the guild already has one in progress.
+    pub async fn create_level_role_sync(&self, guild_id: Id<GuildMarker>, interaction_token: &str) -> Option<LevelRoleSync> {
+        let client = self.get_object().await;
+        let query = "
+            INSERT INTO level_role_sync(guild_id, interaction_token)
+            VALUES($1, $2)
+            ON CONFLICT DO NOTHING
+            RETURNING guild_id, interaction_token, cursor, processed, failures, started_at;
+        ";
+
+        client.query_opt(query, &[&(guild_id.get() as i64), &interaction_token]).await.unwrap().map(LevelRoleSync::from)
+    }
+
+    pub async fn delete_level_role_sync(&self, guild_id: Id<GuildMarker>) {
+        let client = self.get_object().await;
+        let query = "DELETE FROM level_role_sync WHERE guild_id = $1;";
+
+        client.query(query, &[&(guild_id.get() as i64)]).await.unwrap();
+    }
+
+    pub async fn read_level_role_syncs(&self) -> Vec<LevelRoleSync> {
+        let client = self.get_object().await;
+        let query = "SELECT guild_id, interaction_token, cursor, processed, failures, started_at FROM level_role_sync;";
+
+        client.query(query, &[]).await.unwrap().into_iter().map(LevelRoleSync::from).collect()
+    }
+
+    pub async fn update_level_role_sync(&self, guild_id: Id<GuildMarker>, cursor: Id<UserMarker>, processed: u64, failures: u64) {
+        let client = self.get_object().await;
+        let query = "UPDATE level_role_sync SET cursor = $1, processed = $2, failures = $3 WHERE guild_id = $4;";
+
+        client.query(
+            query,
+            &[
+                &(cursor.get() as i64),
+                &(processed as i64),
+                &(failures as i64),
+                &(guild_id.get() as i64)
+            ]
+        ).await.unwrap();
+    }
+}
@@ -1,5 +1,5 @@
-use chrono::NaiveDateTime;
-use crate::database::Database;
+use chrono::{DateTime, Utc};
+use crate::database::{Database, level_role::RoleKind};
 use tokio_postgres::Row;
 use twilight_model::id::{Id, marker::{GuildMarker, UserMarker}};
 
@@ -7,7 +7,7 @@ pub struct Member {
     pub guild_id: Id<GuildMarker>,
     pub member_id: Id<UserMarker>,
     pub message_xp: u64,
-    pub message_xp_updated_at: NaiveDateTime,
+    pub message_xp_updated_at: DateTime<Utc>,
     pub voice_xp: u64,
     pub bio: Option<String>
 }
@@ -18,7 +18,7 @@ impl From<Row> for Member {
             guild_id: Id::new(row.get::<_, i64>(0) as u64),
             member_id: Id::new(row.get::<_, i64>(1) as u64),
             message_xp: row.get::<_, i64>(2) as u64,
-            message_xp_updated_at: row.get::<_, NaiveDateTime>(3),
+            message_xp_updated_at: row.get::<_, DateTime<Utc>>(3),
             voice_xp: row.get::<_, i64>(4) as u64,
             bio: match row.try_get::<_, String>(5) {
                 Ok(value) => Some(value),
@@ -49,9 +49,56 @@ impl Database {
         }
     }
 
+    pub async fn increment_message_xp(&self, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>, xp: u64, cooldown: u32) -> Option<u64> {
+        let client = self.get_object().await;
+        let query = "
+            INSERT INTO member(guild_id, member_id, message_xp)
+            VALUES($1, $2, $3)
+            ON CONFLICT (guild_id, member_id)
+            DO UPDATE SET message_xp = member.message_xp + EXCLUDED.message_xp, message_xp_updated_at = CURRENT_TIMESTAMP
+            WHERE member.message_xp_updated_at <= CURRENT_TIMESTAMP - make_interval(secs => $4)
+            RETURNING message_xp;
+        ";
+
+        match client.query_opt(
+            query,
+            &[
+                &(guild_id.get() as i64),
+                &(member_id.get() as i64),
+                &(xp as i64),
+                &(cooldown as f64)
+            ]
+        ).await {
+            Ok(Some(row)) => Some(row.get::<_, i64>(0) as u64),
+            _ => None
+        }
+    }
+
+    pub async fn increment_voice_xp(&self, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>, xp: u64) -> u64 {
+        let client = self.get_object().await;
+        let query = "
+            INSERT INTO member(guild_id, member_id, voice_xp)
+            VALUES($1, $2, $3)
+            ON CONFLICT (guild_id, member_id)
+            DO UPDATE SET voice_xp = member.voice_xp + EXCLUDED.voice_xp
+            RETURNING voice_xp;
+        ";
+
+        let row = client.query_one(
+            query,
+            &[
+                &(guild_id.get() as i64),
+                &(member_id.get() as i64),
+                &(xp as i64)
+            ]
+        ).await.unwrap();
+
+        row.get::<_, i64>(0) as u64
+    }
+
     pub async fn read_members(&self, guild_id: Id<GuildMarker>) -> Option<Vec<Member>> {
         let client = self.get_object().await;
-        let query = "SELECT * FROM member WHERE guild_id = $1;";
+        let query = "SELECT * FROM member WHERE guild_id = $1 ORDER BY member_id;";
 
         match client.query(query, &[&(guild_id.get() as i64)]).await {
             Ok(rows) => {
@@ -67,14 +114,67 @@ impl Database {
         }
     }
 
-    pub async fn read_xp(&self, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>) -> Option<(u64, NaiveDateTime, u64)> {
+    pub async fn read_leaderboard(&self, guild_id: Id<GuildMarker>, kind: RoleKind, limit: u64, offset: u64) -> Vec<Member> {
+        let client = self.get_object().await;
+        let column = kind.xp_column();
+        let query = format!("
+            SELECT
+                *
+            FROM
+                member
+            WHERE
+                guild_id = $1
+                AND {column} > 0
+            ORDER BY
+                {column} DESC,
+                member_id
+            LIMIT $2
+            OFFSET $3;
+        ");
+        let rows = client.query(&query, &[&(guild_id.get() as i64), &(limit as i64), &(offset as i64)]).await.unwrap();
+
+        rows.into_iter().map(Member::from).collect()
+    }
+
+    pub async fn read_leaderboard_size(&self, guild_id: Id<GuildMarker>, kind: RoleKind) -> u64 {
+        let client = self.get_object().await;
+        let query = format!("SELECT COUNT(*) FROM member WHERE guild_id = $1 AND {} > 0;", kind.xp_column());
+        let row = client.query_one(&query, &[&(guild_id.get() as i64)]).await.unwrap();
+
+        row.get::<_, i64>(0) as u64
+    }
+
+    /// Returns the member's 1-based position on the leaderboard, breaking XP ties by member ID.
+    pub async fn read_rank(&self, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>, kind: RoleKind) -> u64 {
+        let client = self.get_object().await;
+        let column = kind.xp_column();
+        let query = format!("
+            WITH own AS (
+                SELECT
+                    COALESCE((SELECT {column} FROM member WHERE guild_id = $1 AND member_id = $2), 0) AS xp
+            )
+            SELECT
+                COUNT(*) + 1
+            FROM
+                member,
+                own
+            WHERE
+                guild_id = $1
+                AND ({column} > own.xp OR ({column} = own.xp AND member_id < $2));
+        ");
+        let row = client.query_one(&query, &[&(guild_id.get() as i64), &(member_id.get() as i64)]).await.unwrap();
+
+        row.get::<_, i64>(0) as u64
+    }
+
+    pub async fn read_xp(&self, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>) -> Option<(u64, DateTime<Utc>, u64)> {
         let client = self.get_object().await;
         let query = "SELECT message_xp, message_xp_updated_at, voice_xp FROM member WHERE guild_id = $1 AND member_id = $2;";
 
         match client.query_one(query, &[&(guild_id.get() as i64), &(member_id.get() as i64)]).await {
             Ok(row) => Some((
                 row.get::<_, i64>(0) as u64,
-                row.get::<_, NaiveDateTime>(1),
+                row.get::<_, DateTime<Utc>>(1),
                 row.get::<_, i64>(2) as u64,            
             )),
             Err(_) => None
@@ -1,11 +1,19 @@
 pub mod action;
+pub mod custom_action;
 pub mod level_role;
+pub mod level_role_sync;
 pub mod member;
+pub mod role_snapshot;
+pub mod season;
 pub mod setting;
 pub mod shared_role;
+pub mod shared_role_invite;
 pub mod ship;
+pub mod xp_audit;
+pub mod xp_modifier;
 
 pub use action::CountedAction;
+pub use custom_action::CustomAction;
 pub use level_role::LevelRole;
 pub use member::Member;
 pub use setting::Setting;
@@ -23,8 +31,12 @@ pub struct Database {
 
 impl Database {
     pub fn new() -> Self {
+        Self::from_url(&DATABASE_URL)
+    }
+
+    pub fn from_url(url: &str) -> Self {
         let pool = Pool::builder(Manager::from_config(
-            Config::from_str(&DATABASE_URL).unwrap(),
+            Config::from_str(url).unwrap(),
             NoTls,
             ManagerConfig { recycling_method: RecyclingMethod::Fast }
         ))
@@ -44,30 +56,45 @@ impl Database {
     pub async fn create_tables(&self) {
         let client = self.get_object().await;
         let schema_query = "
-            DROP TYPE IF EXISTS module CASCADE;
-            DROP TYPE IF EXISTS role_kind CASCADE;
-
-            CREATE TYPE module AS ENUM ('actions', 'levels', 'shared_roles');
-            CREATE TYPE role_kind AS ENUM ('message', 'voice');
+            DO $$ BEGIN
+                CREATE TYPE module AS ENUM ('actions', 'levels', 'shared_roles');
+            EXCEPTION
+                WHEN duplicate_object THEN NULL;
+            END $$;
+            DO $$ BEGIN
+                CREATE TYPE role_kind AS ENUM ('message', 'voice');
+            EXCEPTION
+                WHEN duplicate_object THEN NULL;
+            END $$;
 
-            CREATE TABLE IF NOT EXISTS public.action (
+            CREATE TABLE IF NOT EXISTS public.action_count (
+                guild_id INT8 NOT NULL,
+                action TEXT NOT NULL,
+                member_id INT8 NOT NULL,
+                recipient_id INT8 NOT NULL,
+                count INT8 NOT NULL DEFAULT 0,
+                first_at TIMESTAMPTZ(3) DEFAULT NULL,
+                last_at TIMESTAMPTZ(3) DEFAULT NULL,
+                CONSTRAINT ck_action_count PRIMARY KEY (guild_id, action, member_id, recipient_id)
+            );
+            CREATE TABLE IF NOT EXISTS public.action_event (
+                id INT8 GENERATED ALWAYS AS IDENTITY,
                 guild_id INT8 NOT NULL,
+                channel_id INT8 NOT NULL,
                 member_id INT8 NOT NULL,
-                recipient_id INT8 NOT NULL,                
-                bite INT2 NOT NULL DEFAULT 0,
-                cuddle INT2 NOT NULL DEFAULT 0,
-                handhold INT2 NOT NULL DEFAULT 0,
-                hug INT2 NOT NULL DEFAULT 0,
-                kill INT2 NOT NULL DEFAULT 0,
-                kiss INT2 NOT NULL DEFAULT 0,
-                pat INT2 NOT NULL DEFAULT 0,
-                pinch INT2 NOT NULL DEFAULT 0,
-                poke INT2 NOT NULL DEFAULT 0,
-                punch INT2 NOT NULL DEFAULT 0,
-                shrug INT2 NOT NULL DEFAULT 0,
-                slap INT2 NOT NULL DEFAULT 0,
-                tickle INT2 NOT NULL DEFAULT 0,
-                CONSTRAINT ck_action PRIMARY KEY (guild_id, member_id, recipient_id)
+                recipient_id INT8 NOT NULL,
+                action TEXT NOT NULL,
+                created_at TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                CONSTRAINT pk_action_event PRIMARY KEY (id)
+            );
+            CREATE TABLE IF NOT EXISTS public.custom_action (
+                guild_id INT8 NOT NULL,
+                name TEXT NOT NULL,
+                phrase TEXT NOT NULL,
+                plural TEXT NOT NULL,
+                self_phrase TEXT NOT NULL,
+                gif_urls TEXT[] NOT NULL DEFAULT '{}',
+                CONSTRAINT ck_custom_action PRIMARY KEY (guild_id, name)
             );
             CREATE TABLE IF NOT EXISTS public.level_role (
                 guild_id INT8 NOT NULL,
@@ -77,6 +104,15 @@ impl Database {
                 is_persistent BOOLEAN NOT NULL DEFAULT FALSE,
                 CONSTRAINT ck_level_role PRIMARY KEY (guild_id, role_id, kind, level)
             );
+            CREATE TABLE IF NOT EXISTS public.level_role_sync (
+                guild_id INT8 NOT NULL,
+                interaction_token TEXT NOT NULL,
+                cursor INT8 DEFAULT NULL,
+                processed INT8 NOT NULL DEFAULT 0,
+                failures INT8 NOT NULL DEFAULT 0,
+                started_at TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                CONSTRAINT pk_level_role_sync PRIMARY KEY (guild_id)
+            );
             CREATE TABLE IF NOT EXISTS public.member (
                 guild_id INT8 NOT NULL,
                 member_id INT8 NOT NULL,
@@ -86,14 +122,43 @@ impl Database {
                 bio TEXT DEFAULT NULL,
                 CONSTRAINT ck_member PRIMARY KEY (guild_id, member_id)
             );
+            CREATE TABLE IF NOT EXISTS public.role_snapshot (
+                guild_id INT8 NOT NULL,
+                member_id INT8 NOT NULL,
+                role_ids INT8[] NOT NULL DEFAULT '{}',
+                created_at TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                CONSTRAINT ck_role_snapshot PRIMARY KEY (guild_id, member_id)
+            );
+            CREATE TABLE IF NOT EXISTS public.season (
+                id INT8 GENERATED ALWAYS AS IDENTITY,
+                guild_id INT8 NOT NULL,
+                name TEXT NOT NULL,
+                ended_at TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                CONSTRAINT pk_season PRIMARY KEY (id)
+            );
+            CREATE TABLE IF NOT EXISTS public.season_archive (
+                season_id INT8 NOT NULL,
+                guild_id INT8 NOT NULL,
+                member_id INT8 NOT NULL,
+                message_xp INT8 NOT NULL,
+                voice_xp INT8 NOT NULL,
+                CONSTRAINT ck_season_archive PRIMARY KEY (season_id, member_id),
+                CONSTRAINT fk_season_archive_season_id FOREIGN KEY (season_id) REFERENCES public.season (id) ON DELETE CASCADE
+            );
             CREATE TABLE IF NOT EXISTS public.setting (
                 guild_id INT8 NOT NULL,
-                enabled_modules module[] NOT NULL DEFAULT '{}',                
+                enabled_modules module[] NOT NULL DEFAULT '{actions,levels,shared_roles}',                
                 member_role_ids INT8[] NOT NULL DEFAULT '{}',
                 message_levels_enabled BOOLEAN NOT NULL DEFAULT FALSE,
                 voice_levels_enabled BOOLEAN NOT NULL DEFAULT FALSE,
                 rank_color INT4 NOT NULL DEFAULT 16758725,
                 should_keep_roles BOOLEAN NOT NULL DEFAULT FALSE,
+                kept_role_denied_ids INT8[] NOT NULL DEFAULT '{}',
+                level_curve TEXT NOT NULL DEFAULT 'polynomial',
+                level_table INT8[] NOT NULL DEFAULT '{}',
+                level_up_destination TEXT NOT NULL DEFAULT 'current',
+                level_up_channel_id INT8 DEFAULT NULL,
+                level_up_template TEXT NOT NULL DEFAULT 'GG {user}, you just reached {kind} level **{level}**!',
                 CONSTRAINT pk_setting PRIMARY KEY (guild_id)
             );
             CREATE TABLE IF NOT EXISTS public.shared_role (
@@ -102,6 +167,15 @@ impl Database {
                 owner_ids INT8[] NOT NULL DEFAULT '{}',
                 CONSTRAINT ck_shared_role PRIMARY KEY (guild_id, role_id)
             );
+            CREATE TABLE IF NOT EXISTS public.shared_role_invite (
+                guild_id INT8 NOT NULL,
+                role_id INT8 NOT NULL,
+                inviter_id INT8 NOT NULL,
+                invitee_id INT8 NOT NULL,
+                expires_at TIMESTAMPTZ(3) NOT NULL,
+                CONSTRAINT ck_shared_role_invite PRIMARY KEY (guild_id, role_id, invitee_id),
+                CONSTRAINT fk_shared_role_invite_shared_role FOREIGN KEY (guild_id, role_id) REFERENCES public.shared_role (guild_id, role_id) ON DELETE CASCADE
+            );
             CREATE TABLE IF NOT EXISTS public.ship (
                 guild_id INT8 NOT NULL,
                 id_one INT8 NOT NULL,
@@ -109,11 +183,144 @@ impl Database {
                 name TEXT NOT NULL DEFAULT 'Bluenose',
                 created_at TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP
             );
+            CREATE TABLE IF NOT EXISTS public.xp_audit (
+                id INT8 GENERATED ALWAYS AS IDENTITY,
+                guild_id INT8 NOT NULL,
+                moderator_id INT8 NOT NULL,
+                member_id INT8 NOT NULL,
+                kind role_kind NOT NULL,
+                operation TEXT NOT NULL,
+                amount INT8 NOT NULL,
+                old_xp INT8 NOT NULL,
+                new_xp INT8 NOT NULL,
+                created_at TIMESTAMPTZ(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                CONSTRAINT pk_xp_audit PRIMARY KEY (id)
+            );
+            CREATE TABLE IF NOT EXISTS public.xp_event (
+                id INT8 GENERATED ALWAYS AS IDENTITY,
+                guild_id INT8 NOT NULL,
+                name TEXT NOT NULL,
+                multiplier INT2 NOT NULL,
+                starts_at TIMESTAMPTZ(3) NOT NULL,
+                ends_at TIMESTAMPTZ(3) NOT NULL,
+                CONSTRAINT pk_xp_event PRIMARY KEY (id)
+            );
+            CREATE TABLE IF NOT EXISTS public.xp_modifier (
+                guild_id INT8 NOT NULL,
+                target_id INT8 NOT NULL,
+                target TEXT NOT NULL,
+                multiplier INT2 NOT NULL,
+                CONSTRAINT ck_xp_modifier PRIMARY KEY (guild_id, target_id)
+            );
+
+            -- Every start used to drop the enum types with CASCADE, which took setting.enabled_modules and
+            -- level_role.kind with them, along with level_role's primary key. Level roles from before are message
+            -- roles, and the primary key comes back once the rows that only differed by kind are merged.
+            ALTER TABLE public.setting ADD COLUMN IF NOT EXISTS enabled_modules module[] NOT NULL DEFAULT '{actions,levels,shared_roles}';
+            ALTER TABLE public.level_role ADD COLUMN IF NOT EXISTS kind role_kind NOT NULL DEFAULT 'message';
+            ALTER TABLE public.level_role ALTER COLUMN kind DROP DEFAULT;
+            DO $$ BEGIN
+                IF NOT EXISTS (SELECT FROM pg_constraint WHERE conname = 'ck_level_role') THEN
+                    DELETE FROM public.level_role AS duplicate
+                    USING public.level_role AS kept
+                    WHERE
+                        duplicate.guild_id = kept.guild_id
+                        AND duplicate.role_id = kept.role_id
+                        AND duplicate.kind = kept.kind
+                        AND duplicate.level = kept.level
+                        AND duplicate.ctid > kept.ctid;
+
+                    ALTER TABLE public.level_role ADD CONSTRAINT ck_level_role PRIMARY KEY (guild_id, role_id, kind, level);
+                END IF;
+            END $$;
+
+            ALTER TABLE public.setting ADD COLUMN IF NOT EXISTS level_curve TEXT NOT NULL DEFAULT 'polynomial';
+            ALTER TABLE public.setting ADD COLUMN IF NOT EXISTS level_table INT8[] NOT NULL DEFAULT '{}';
+            ALTER TABLE public.setting ADD COLUMN IF NOT EXISTS level_up_destination TEXT NOT NULL DEFAULT 'current';
+            ALTER TABLE public.setting ADD COLUMN IF NOT EXISTS level_up_channel_id INT8 DEFAULT NULL;
+            ALTER TABLE public.setting ADD COLUMN IF NOT EXISTS level_up_template TEXT NOT NULL DEFAULT 'GG {user}, you just reached {kind} level **{level}**!';
+            ALTER TABLE public.setting ADD COLUMN IF NOT EXISTS kept_role_denied_ids INT8[] NOT NULL DEFAULT '{}';
+            ALTER TABLE public.setting ADD COLUMN IF NOT EXISTS log_channel_id INT8 DEFAULT NULL;
+            ALTER TABLE public.setting ADD COLUMN IF NOT EXISTS hide_disabled_commands BOOLEAN NOT NULL DEFAULT FALSE;
+            ALTER TABLE public.setting ADD COLUMN IF NOT EXISTS member_role_delay INT4 NOT NULL DEFAULT 0;
+            ALTER TABLE public.setting ADD COLUMN IF NOT EXISTS member_role_after_screening BOOLEAN NOT NULL DEFAULT FALSE;
 
+            -- Modules used to default to disabled while nothing enforced them, so guilds that kept the column from
+            -- before enforcement start with every module enabled. The column exists by now, either restored above
+            -- or kept, and this runs once, as it changes the default it checks for.
+            DO $$ BEGIN
+                IF (
+                    SELECT column_default FROM information_schema.columns
+                    WHERE table_schema = 'public' AND table_name = 'setting' AND column_name = 'enabled_modules'
+                ) = '''{}''::module[]' THEN
+                    UPDATE public.setting SET enabled_modules = '{actions,levels,shared_roles}';
+                    ALTER TABLE public.setting ALTER COLUMN enabled_modules SET DEFAULT '{actions,levels,shared_roles}';
+                END IF;
+            END $$;
+
+            -- Action counts used to live in one INT2 column per action on the action table. They move into
+            -- action_count; counts from before the event log have no timestamps. The old table is kept as action_legacy.
+            DO $$ BEGIN
+                IF EXISTS (SELECT FROM information_schema.tables WHERE table_schema = 'public' AND table_name = 'action') THEN
+                    INSERT INTO public.action_count (guild_id, action, member_id, recipient_id, count)
+                    SELECT
+                        legacy.guild_id,
+                        counts.action,
+                        legacy.member_id,
+                        legacy.recipient_id,
+                        counts.count
+                    FROM
+                        public.action AS legacy,
+                        LATERAL (
+                            VALUES
+                                ('bite', legacy.bite),
+                                ('cuddle', legacy.cuddle),
+                                ('handhold', legacy.handhold),
+                                ('hug', legacy.hug),
+                                ('kill', legacy.kill),
+                                ('kiss', legacy.kiss),
+                                ('pat', legacy.pat),
+                                ('pinch', legacy.pinch),
+                                ('poke', legacy.poke),
+                                ('punch', legacy.punch),
+                                ('shrug', legacy.shrug),
+                                ('slap', legacy.slap),
+                                ('tickle', legacy.tickle)
+                        ) AS counts (action, count)
+                    WHERE
+                        counts.count > 0
+                    ON CONFLICT DO NOTHING;
+
+                    ALTER TABLE public.action RENAME TO action_legacy;
+                END IF;
+            END $$;
+
+            CREATE INDEX IF NOT EXISTS idx_action_count_guild_id_member_id_recipient_id ON public.action_count USING btree (guild_id, member_id, recipient_id);
+            CREATE INDEX IF NOT EXISTS idx_action_event_guild_id_created_at ON public.action_event USING btree (guild_id, created_at);
+            CREATE INDEX IF NOT EXISTS idx_member_guild_id_message_xp ON public.member USING btree (guild_id, message_xp DESC, member_id);
+            CREATE INDEX IF NOT EXISTS idx_member_guild_id_voice_xp ON public.member USING btree (guild_id, voice_xp DESC, member_id);
+            CREATE INDEX IF NOT EXISTS idx_season_guild_id_ended_at ON public.season USING btree (guild_id, ended_at DESC);
             CREATE UNIQUE INDEX IF NOT EXISTS idx_ship_guild_id_id_one ON public.ship USING btree (guild_id, id_one);
             CREATE UNIQUE INDEX IF NOT EXISTS idx_ship_guild_id_id_two ON public.ship USING btree (guild_id, id_two);
+            CREATE INDEX IF NOT EXISTS idx_xp_audit_guild_id_member_id ON public.xp_audit USING btree (guild_id, member_id);
+            CREATE INDEX IF NOT EXISTS idx_xp_event_guild_id_ends_at ON public.xp_event USING btree (guild_id, ends_at);
         ";
 
         client.batch_execute(schema_query).await.unwrap();
     }
-}
\ No newline at end of file
+}
+
+/// Connects to the database named by `TEST_DATABASE_URL` with the schema in place. Tests using it are ignored by
+/// default and run with `cargo test -- --ignored`.
+#[cfg(test)]
+pub async fn test_database() -> Database {
+    use tokio::sync::OnceCell;
+
+    static SCHEMA: OnceCell<()> = OnceCell::const_new();
+
+    let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a Postgres database to run database tests");
+
+    SCHEMA.get_or_init(|| async { Database::from_url(&url).create_tables().await }).await;
+
+    Database::from_url(&url)
+}
@@ -0,0 +1,33 @@
+use crate::database::Database;
+use twilight_model::id::{Id, marker::{GuildMarker, RoleMarker, UserMarker}};
+
+impl Database {
+    pub async fn create_role_snapshot(&self, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>, role_ids: &[Id<RoleMarker>]) {
+        let client = self.get_object().await;
+        let query = "
+            INSERT INTO role_snapshot(guild_id, member_id, role_ids)
+            VALUES($1, $2, $3)
+            ON CONFLICT (guild_id, member_id) DO UPDATE SET role_ids = EXCLUDED.role_ids, created_at = CURRENT_TIMESTAMP;
+        ";
+
+        client.query(
+            query,
+            &[
+                &(guild_id.get() as i64),
+                &(member_id.get() as i64),
+                &role_ids.iter().map(|id| id.get() as i64).collect::<Vec<i64>>()
+            ]
+        ).await.unwrap();
+    }
+
+    /// Removes the member's snapshot and returns the roles it held, so a snapshot is only ever restored once.
+    pub async fn take_role_snapshot(&self, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>) -> Option<Vec<Id<RoleMarker>>> {
+        let client = self.get_object().await;
+        let query = "DELETE FROM role_snapshot WHERE guild_id = $1 AND member_id = $2 RETURNING role_ids;";
+
+        match client.query_one(query, &[&(guild_id.get() as i64), &(member_id.get() as i64)]).await {
+            Ok(row) => Some(row.get::<_, Vec<i64>>(0).into_iter().map(|id| Id::new(id as u64)).collect()),
+            Err(_) => None
+        }
+    }
+}
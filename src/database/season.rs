@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use crate::database::Database;
+use tokio_postgres::Row;
+use twilight_model::id::{Id, marker::{GuildMarker, UserMarker}};
+
+pub struct Season {
+    pub name: String,
+    pub ended_at: DateTime<Utc>,
+    pub message_winner: Option<(Id<UserMarker>, u64)>,
+    pub voice_winner: Option<(Id<UserMarker>, u64)>
+}
+
+impl From<Row> for Season {
+    fn from(row: Row) -> Self {
+        let winner = |id_index: usize, xp_index: usize| {
+            row.get::<_, Option<i64>>(id_index).map(|id| (Id::new(id as u64), row.get::<_, i64>(xp_index) as u64))
+        };
+
+        Self {
+            name: row.get(0),
+            ended_at: row.get::<_, DateTime<Utc>>(1),
+            message_winner: winner(2, 3),
+            voice_winner: winner(4, 5)
+        }
+    }
+}
+
+impl Database {
+    /// Archives every member's XP under a new season and scales it down to `keep_percent`, as a single transaction.
+    /// Returns the season ID and the number of archived members.
+    pub async fn end_season(&self, guild_id: Id<GuildMarker>, name: String, keep_percent: u8) -> (i64, u64) {
+        let mut client = self.get_object().await;
+        let transaction = client.transaction().await.unwrap();
+        let season_query = "INSERT INTO season(guild_id, name) VALUES($1, $2) RETURNING id;";
+        let reset_query = "
+            WITH old AS (
+                SELECT member_id, message_xp, voice_xp
+                FROM member
+                WHERE guild_id = $2 AND (message_xp > 0 OR voice_xp > 0)
+                FOR UPDATE
+            ), archived AS (
+                INSERT INTO season_archive(season_id, guild_id, member_id, message_xp, voice_xp)
+                SELECT $1, $2, member_id, message_xp, voice_xp FROM old
+            )
+            UPDATE
+                member
+            SET
+                message_xp = DIV(old.message_xp::NUMERIC * $3::INT8, 100)::INT8,
+                voice_xp = DIV(old.voice_xp::NUMERIC * $3::INT8, 100)::INT8
+            FROM
+                old
+            WHERE
+                member.guild_id = $2
+                AND member.member_id = old.member_id;
+        ";
+        let season_id = transaction
+            .query_one(season_query, &[&(guild_id.get() as i64), &name])
+            .await
+            .unwrap()
+            .get::<_, i64>(0);
+        let archived = transaction
+            .execute(reset_query, &[&season_id, &(guild_id.get() as i64), &(keep_percent as i64)])
+            .await
+            .unwrap();
+
+        transaction.commit().await.unwrap();
+
+        (season_id, archived)
+    }
+
+    pub async fn read_season_count(&self, guild_id: Id<GuildMarker>) -> u64 {
+        let client = self.get_object().await;
+        let query = "SELECT COUNT(*) FROM season WHERE guild_id = $1;";
+
+        client.query_one(query, &[&(guild_id.get() as i64)]).await.unwrap().get::<_, i64>(0) as u64
+    }
+
+    /// Reads the most recent seasons along with the top member of each kind.
+    pub async fn read_seasons(&self, guild_id: Id<GuildMarker>, limit: i64) -> Vec<Season> {
+        let client = self.get_object().await;
+        let query = "
+            SELECT
+                season.name,
+                season.ended_at,
+                message_winner.member_id,
+                message_winner.message_xp,
+                voice_winner.member_id,
+                voice_winner.voice_xp
+            FROM
+                season
+            LEFT JOIN LATERAL (
+                SELECT member_id, message_xp
+                FROM season_archive
+                WHERE season_id = season.id AND message_xp > 0
+                ORDER BY message_xp DESC, member_id
+                LIMIT 1
+            ) AS message_winner ON TRUE
+            LEFT JOIN LATERAL (
+                SELECT member_id, voice_xp
+                FROM season_archive
+                WHERE season_id = season.id AND voice_xp > 0
+                ORDER BY voice_xp DESC, member_id
+                LIMIT 1
+            ) AS voice_winner ON TRUE
+            WHERE
+                season.guild_id = $1
+            ORDER BY
+                season.ended_at DESC
+            LIMIT $2;
+        ";
+
+        client.query(query, &[&(guild_id.get() as i64), &limit]).await.unwrap().into_iter().map(Season::from).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        database::{level_role::RoleKind, test_database, xp_audit::XpOperation},
+        levels::import::MAX_XP
+    };
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn scales_xp_near_the_storable_limit() {
+        let database = test_database().await;
+        let (guild_id, member_id) = (Id::new(1_000_000_012), Id::new(12));
+
+        database.update_xp_with_audit(guild_id, Id::new(1), &[member_id], RoleKind::Message, XpOperation::Set(MAX_XP)).await;
+        database.update_xp_with_audit(guild_id, Id::new(1), &[member_id], RoleKind::Voice, XpOperation::Set(199)).await;
+        database.end_season(guild_id, "Overflow".to_string(), 50).await;
+
+        let (message_xp, _, voice_xp) = database.read_xp(guild_id, member_id).await.unwrap();
+
+        assert_eq!((message_xp, voice_xp), (MAX_XP / 2, 99));
+    }
+}
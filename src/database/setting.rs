@@ -1,25 +1,42 @@
-use crate::database::Database;
+use crate::{database::Database, levels::LevelCurve};
 use tokio_postgres::Row;
-use twilight_model::id::{Id, marker::{GuildMarker, UserMarker}};
+use twilight_model::id::{Id, marker::{ChannelMarker, GuildMarker, RoleMarker}};
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Module {
     Actions,
     Levels,
     SharedRoles
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum LevelUpDestination {
+    Current,
+    Channel(Id<ChannelMarker>),
+    Direct,
+    Disabled
+}
+
 pub struct Setting {
     pub guild_id: Id<GuildMarker>,
     pub enabled_modules: Vec<Module>,
-    pub member_role_ids: Vec<Id<UserMarker>>,
+    pub member_role_ids: Vec<Id<RoleMarker>>,
     pub message_levels_enabled: bool,
     pub voice_levels_enabled: bool,
     pub rank_color: u32,
-    pub should_keep_roles: bool
+    pub should_keep_roles: bool,
+    pub level_curve: LevelCurve,
+    pub level_up_destination: LevelUpDestination,
+    pub level_up_template: String,
+    pub kept_role_denied_ids: Vec<Id<RoleMarker>>,
+    pub log_channel_id: Option<Id<ChannelMarker>>,
+    pub hide_disabled_commands: bool,
+    pub member_role_delay: u32,
+    pub member_role_after_screening: bool
 }
 
 impl Module {
-    fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             Module::Actions => "actions",
             Module::Levels => "levels",
@@ -28,6 +45,32 @@ impl Module {
     }
 }
 
+impl LevelUpDestination {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LevelUpDestination::Current => "current",
+            LevelUpDestination::Channel(_) => "channel",
+            LevelUpDestination::Direct => "direct",
+            LevelUpDestination::Disabled => "disabled"
+        }
+    }
+
+    fn from_parts(name: &str, channel_id: Option<i64>) -> Self {
+        match (name, channel_id) {
+            ("current", _) => LevelUpDestination::Current,
+            ("channel", Some(channel_id)) => LevelUpDestination::Channel(Id::new(channel_id as u64)),
+            ("direct", _) => LevelUpDestination::Direct,
+            _ => LevelUpDestination::Disabled
+        }
+    }
+}
+
+impl Setting {
+    pub fn is_enabled(&self, module: Module) -> bool {
+        self.enabled_modules.contains(&module)
+    }
+}
+
 impl From<Row> for Setting {
     fn from(row: Row) -> Self {
         Self {
@@ -43,7 +86,18 @@ impl From<Row> for Setting {
             message_levels_enabled: row.get(3),
             voice_levels_enabled: row.get(4),
             rank_color: row.get::<_, i32>(5) as u32,
-            should_keep_roles: row.get(6)
+            should_keep_roles: row.get(6),
+            level_curve: LevelCurve::from_parts(
+                row.get(7),
+                row.get::<_, Vec<i64>>(8).into_iter().map(|xp| xp as u64).collect()
+            ),
+            level_up_destination: LevelUpDestination::from_parts(row.get(9), row.get(10)),
+            level_up_template: row.get(11),
+            kept_role_denied_ids: row.get::<_, Vec<i64>>(12).into_iter().map(|id| Id::new(id as u64)).collect(),
+            log_channel_id: row.get::<_, Option<i64>>(13).map(|id| Id::new(id as u64)),
+            hide_disabled_commands: row.get(14),
+            member_role_delay: row.get::<_, i32>(15) as u32,
+            member_role_after_screening: row.get(16)
         }
     }
 }
@@ -65,7 +119,30 @@ impl Database {
 
     pub async fn read_setting(&self, guild_id: Id<GuildMarker>) -> Option<Setting> {
         let client = self.get_object().await;
-        let query = "SELECT * FROM setting WHERE guild_id = $1;";
+        let query = "
+            SELECT
+                guild_id,
+                enabled_modules::TEXT[],
+                member_role_ids,
+                message_levels_enabled,
+                voice_levels_enabled,
+                rank_color,
+                should_keep_roles,
+                level_curve,
+                level_table,
+                level_up_destination,
+                level_up_channel_id,
+                level_up_template,
+                kept_role_denied_ids,
+                log_channel_id,
+                hide_disabled_commands,
+                member_role_delay,
+                member_role_after_screening
+            FROM
+                setting
+            WHERE
+                guild_id = $1;
+        ";
 
         match client.query_one(query, &[&(guild_id.get() as i64)]).await {
             Ok(row) => Some(row.into()),
@@ -73,19 +150,75 @@ impl Database {
         }
     }
 
-    pub async fn update_enabled_modules(&self, guild_id: Id<GuildMarker>, enabled_modules: Vec<String>) {
+    pub async fn update_enabled_modules(&self, guild_id: Id<GuildMarker>, enabled_modules: &[Module]) {
+        let client = self.get_object().await;
+        let query = "UPDATE setting SET enabled_modules = $2::TEXT[]::module[] WHERE guild_id = $1;";
+
+        client.query(
+            query,
+            &[
+                &(guild_id.get() as i64),
+                &enabled_modules.iter().map(|module| module.as_str()).collect::<Vec<&str>>()
+            ]
+        ).await.unwrap();
+    }
+
+    pub async fn update_hide_disabled_commands(&self, guild_id: Id<GuildMarker>, state: bool) {
+        let client = self.get_object().await;
+        let query = "UPDATE setting SET hide_disabled_commands = $1 WHERE guild_id = $2;";
+
+        client.query(query, &[&state, &(guild_id.get() as i64)]).await.unwrap();
+    }
+
+    pub async fn update_kept_role_denied_ids(&self, guild_id: Id<GuildMarker>, role_ids: &[Id<RoleMarker>]) {
         let client = self.get_object().await;
-        let query = "UPDATE setting SET enabled_modules = $2 WHERE guild_id = $1;";
+        let query = "UPDATE setting SET kept_role_denied_ids = $1 WHERE guild_id = $2;";
 
         client.query(
             query,
             &[
-                &enabled_modules.into_iter().map(|module| module).collect::<Vec<String>>(),
+                &role_ids.iter().map(|id| id.get() as i64).collect::<Vec<i64>>(),
                 &(guild_id.get() as i64)
             ]
         ).await.unwrap();
     }
 
+    pub async fn update_level_curve(&self, guild_id: Id<GuildMarker>, level_curve: &LevelCurve) {
+        let client = self.get_object().await;
+        let query = "UPDATE setting SET level_curve = $1, level_table = $2 WHERE guild_id = $3;";
+        let level_table = match level_curve {
+            LevelCurve::Table(table) => table.iter().map(|xp| *xp as i64).collect(),
+            _ => Vec::new()
+        };
+
+        client.query(query, &[&level_curve.as_str(), &level_table, &(guild_id.get() as i64)]).await.unwrap();
+    }
+
+    pub async fn update_level_up_destination(&self, guild_id: Id<GuildMarker>, destination: LevelUpDestination) {
+        let client = self.get_object().await;
+        let query = "UPDATE setting SET level_up_destination = $1, level_up_channel_id = $2 WHERE guild_id = $3;";
+        let channel_id = match destination {
+            LevelUpDestination::Channel(channel_id) => Some(channel_id.get() as i64),
+            _ => None
+        };
+
+        client.query(query, &[&destination.as_str(), &channel_id, &(guild_id.get() as i64)]).await.unwrap();
+    }
+
+    pub async fn update_level_up_template(&self, guild_id: Id<GuildMarker>, template: String) {
+        let client = self.get_object().await;
+        let query = "UPDATE setting SET level_up_template = $1 WHERE guild_id = $2;";
+
+        client.query(query, &[&template, &(guild_id.get() as i64)]).await.unwrap();
+    }
+
+    pub async fn update_log_channel_id(&self, guild_id: Id<GuildMarker>, channel_id: Option<Id<ChannelMarker>>) {
+        let client = self.get_object().await;
+        let query = "UPDATE setting SET log_channel_id = $1 WHERE guild_id = $2;";
+
+        client.query(query, &[&channel_id.map(|id| id.get() as i64), &(guild_id.get() as i64)]).await.unwrap();
+    }
+
     pub async fn update_message_levels_enabled(&self, guild_id: Id<GuildMarker>, state: bool) {
         let client = self.get_object().await;
         let query = "UPDATE setting SET message_levels_enabled = $1 WHERE guild_id = $2;";
@@ -93,14 +226,28 @@ impl Database {
         client.query(query, &[&state, &(guild_id.get() as i64)]).await.unwrap();
     }
 
-    pub async fn update_member_role_ids(&self, guild_id: Id<GuildMarker>, member_role_ids: Vec<Id<UserMarker>>) {
+    pub async fn update_member_role_after_screening(&self, guild_id: Id<GuildMarker>, state: bool) {
+        let client = self.get_object().await;
+        let query = "UPDATE setting SET member_role_after_screening = $1 WHERE guild_id = $2;";
+
+        client.query(query, &[&state, &(guild_id.get() as i64)]).await.unwrap();
+    }
+
+    pub async fn update_member_role_delay(&self, guild_id: Id<GuildMarker>, seconds: u32) {
         let client = self.get_object().await;
-        let query = "UPDATE setting SET member_role_ids = $2 WHERE guild_id = $1;";
+        let query = "UPDATE setting SET member_role_delay = $1 WHERE guild_id = $2;";
+
+        client.query(query, &[&(seconds as i32), &(guild_id.get() as i64)]).await.unwrap();
+    }
+
+    pub async fn update_member_role_ids(&self, guild_id: Id<GuildMarker>, member_role_ids: &[Id<RoleMarker>]) {
+        let client = self.get_object().await;
+        let query = "UPDATE setting SET member_role_ids = $1 WHERE guild_id = $2;";
 
         client.query(
             query,
             &[
-                &member_role_ids.into_iter().map(|id| id.get() as i64).collect::<Vec<i64>>(),
+                &member_role_ids.iter().map(|id| id.get() as i64).collect::<Vec<i64>>(),
                 &(guild_id.get() as i64)
             ]
         ).await.unwrap();
@@ -126,4 +273,30 @@ impl Database {
 
         client.query(query, &[&state, &(guild_id.get() as i64)]).await.unwrap();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_database;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn round_trips_enabled_modules() {
+        let database = test_database().await;
+        let guild_id = Id::new(1_000_000_018);
+
+        database.delete_setting(guild_id).await;
+        database.create_setting(guild_id).await;
+
+        for modules in [vec![Module::Levels], vec![Module::Actions, Module::SharedRoles], vec![]] {
+            database.update_enabled_modules(guild_id, &modules).await;
+
+            let setting = database.read_setting(guild_id).await.unwrap();
+
+            assert_eq!(setting.enabled_modules, modules);
+        }
+
+        database.delete_setting(guild_id).await;
+    }
+}
@@ -33,11 +33,11 @@ impl Database {
         ).await.unwrap();
     }
 
-    pub async fn delete_shared_role(&self, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>) {
+    pub async fn delete_shared_role(&self, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>) -> bool {
         let client = self.get_object().await;
         let query = "DELETE FROM shared_role WHERE guild_id = $1 AND role_id = $2;";
 
-        client.query(query, &[&(guild_id.get() as i64), &(role_id.get() as i64)]).await.unwrap();
+        client.execute(query, &[&(guild_id.get() as i64), &(role_id.get() as i64)]).await.unwrap() > 0
     }
 
     pub async fn delete_shared_roles(&self, guild_id: Id<GuildMarker>) {
@@ -75,17 +75,81 @@ impl Database {
         }
     }
 
-    pub async fn update_owner_ids(&self, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>, owner_ids: Vec<Id<UserMarker>>) {
+    /// Appends an owner in place, so concurrent changes to the same role cannot overwrite each other. Returns
+    /// `false` when the member already owns the role or it has `max_owners` owners.
+    pub async fn add_shared_role_owner(&self, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>, owner_id: Id<UserMarker>, max_owners: usize) -> bool {
         let client = self.get_object().await;
-        let query = "UPDATE shared_role SET owner_ids = $3 WHERE guild_id = $1 AND role_id = $2;";
+        let query = "
+            UPDATE shared_role
+            SET owner_ids = array_append(owner_ids, $3)
+            WHERE guild_id = $1 AND role_id = $2 AND NOT $3 = ANY(owner_ids) AND cardinality(owner_ids) < $4;
+        ";
 
-        client.query(
+        client.execute(
             query,
             &[
-                &owner_ids.into_iter().map(|id| id.get() as i64).collect::<Vec<i64>>(),
                 &(guild_id.get() as i64),
-                &(role_id.get() as i64)                
+                &(role_id.get() as i64),
+                &(owner_id.get() as i64),
+                &(max_owners as i32)
             ]
-        ).await.unwrap();
+        ).await.unwrap() > 0
+    }
+
+    /// Removes an owner in place and returns the remaining owners, or `None` when the member did not own the role.
+    pub async fn remove_shared_role_owner(&self, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>, owner_id: Id<UserMarker>) -> Option<Vec<Id<UserMarker>>> {
+        let client = self.get_object().await;
+        let query = "
+            UPDATE shared_role
+            SET owner_ids = array_remove(owner_ids, $3)
+            WHERE guild_id = $1 AND role_id = $2 AND $3 = ANY(owner_ids)
+            RETURNING owner_ids;
+        ";
+
+        client
+            .query_opt(query, &[&(guild_id.get() as i64), &(role_id.get() as i64), &(owner_id.get() as i64)])
+            .await
+            .unwrap()
+            .map(|row| row.get::<_, Vec<i64>>(0).into_iter().map(|id| Id::new(id as u64)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::test_database;
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn adds_and_removes_owners_in_place() {
+        let database = test_database().await;
+        let (guild_id, role_id) = (Id::new(1_000_000_015), Id::new(15));
+
+        database.delete_shared_role(guild_id, role_id).await;
+        database.create_shared_role(guild_id, role_id, vec![Id::new(100)]).await;
+
+        assert!(database.add_shared_role_owner(guild_id, role_id, Id::new(200), 3).await);
+        assert!(!database.add_shared_role_owner(guild_id, role_id, Id::new(200), 3).await);
+        assert!(database.add_shared_role_owner(guild_id, role_id, Id::new(300), 3).await);
+        assert!(!database.add_shared_role_owner(guild_id, role_id, Id::new(400), 3).await);
+
+        assert_eq!(database.remove_shared_role_owner(guild_id, role_id, Id::new(200)).await, Some(vec![Id::new(100), Id::new(300)]));
+        assert_eq!(database.remove_shared_role_owner(guild_id, role_id, Id::new(200)).await, None);
+
+        database.delete_shared_role(guild_id, role_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn leaves_no_owners_after_the_last_one_leaves() {
+        let database = test_database().await;
+        let (guild_id, role_id) = (Id::new(1_000_000_017), Id::new(17));
+
+        database.delete_shared_role(guild_id, role_id).await;
+        database.create_shared_role(guild_id, role_id, vec![Id::new(100)]).await;
+
+        assert_eq!(database.remove_shared_role_owner(guild_id, role_id, Id::new(100)).await, Some(vec![]));
+
+        database.delete_shared_role(guild_id, role_id).await;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use crate::database::Database;
+use tokio_postgres::Row;
+use twilight_model::id::{Id, marker::{GuildMarker, RoleMarker, UserMarker}};
+
+pub struct SharedRoleInvite {
+    pub inviter_id: Id<UserMarker>,
+    pub expires_at: DateTime<Utc>
+}
+
+impl From<Row> for SharedRoleInvite {
+    fn from(row: Row) -> Self {
+        Self {
+            inviter_id: Id::new(row.get::<_, i64>(0) as u64),
+            expires_at: row.get::<_, DateTime<Utc>>(1)
+        }
+    }
+}
+
+impl Database {
+    /// Stores an invite, replacing any earlier invite to the same role so its expiry starts over.
+    pub async fn create_shared_role_invite(
+        &self,
+        guild_id: Id<GuildMarker>,
+        role_id: Id<RoleMarker>,
+        inviter_id: Id<UserMarker>,
+        invitee_id: Id<UserMarker>,
+        expires_at: DateTime<Utc>
+    ) {
+        let client = self.get_object().await;
+        let query = "
+            INSERT INTO shared_role_invite(guild_id, role_id, inviter_id, invitee_id, expires_at)
+            VALUES($1, $2, $3, $4, $5)
+            ON CONFLICT (guild_id, role_id, invitee_id) DO UPDATE SET inviter_id = EXCLUDED.inviter_id, expires_at = EXCLUDED.expires_at;
+        ";
+
+        client.query(
+            query,
+            &[
+                &(guild_id.get() as i64),
+                &(role_id.get() as i64),
+                &(inviter_id.get() as i64),
+                &(invitee_id.get() as i64),
+                &expires_at
+            ]
+        ).await.unwrap();
+    }
+
+    pub async fn delete_expired_shared_role_invites(&self) {
+        let client = self.get_object().await;
+        let query = "DELETE FROM shared_role_invite WHERE expires_at <= CURRENT_TIMESTAMP;";
+
+        client.query(query, &[]).await.unwrap();
+    }
+
+    /// Removes the invite and returns it, so every invite is answered at most once. Expired invites are returned too,
+    /// and it is up to the caller to check `expires_at`.
+    pub async fn take_shared_role_invite(&self, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>, invitee_id: Id<UserMarker>) -> Option<SharedRoleInvite> {
+        let client = self.get_object().await;
+        let query = "
+            DELETE FROM shared_role_invite
+            WHERE guild_id = $1 AND role_id = $2 AND invitee_id = $3
+            RETURNING inviter_id, expires_at;
+        ";
+
+        match client.query_one(query, &[&(guild_id.get() as i64), &(role_id.get() as i64), &(invitee_id.get() as i64)]).await {
+            Ok(row) => Some(row.into()),
+            Err(_) => None
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{database::{Database, level_role::RoleKind}, levels::import::MergeStrategy};
+use std::collections::HashMap;
+use twilight_model::id::{Id, marker::{GuildMarker, UserMarker}};
+
+#[derive(Clone, Copy)]
+pub enum XpOperation {
+    Add(u64),
+    Remove(u64),
+    Reset,
+    Set(u64)
+}
+
+pub struct XpChange {
+    pub member_id: Id<UserMarker>,
+    pub old_xp: u64,
+    pub new_xp: u64
+}
+
+impl XpOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            XpOperation::Add(_) => "add",
+            XpOperation::Remove(_) => "remove",
+            XpOperation::Reset => "reset",
+            XpOperation::Set(_) => "set"
+        }
+    }
+
+    fn amount(&self) -> u64 {
+        match self {
+            XpOperation::Add(amount) | XpOperation::Remove(amount) | XpOperation::Set(amount) => *amount,
+            XpOperation::Reset => 0
+        }
+    }
+}
+
+impl Database {
+    /// Applies `operation` to every member in `member_ids` and records each change in the audit log, all within one
+    /// transaction.
+    pub async fn update_xp_with_audit(
+        &self,
+        guild_id: Id<GuildMarker>,
+        moderator_id: Id<UserMarker>,
+        member_ids: &[Id<UserMarker>],
+        kind: RoleKind,
+        operation: XpOperation
+    ) -> Vec<XpChange> {
+        let mut client = self.get_object().await;
+        let transaction = client.transaction().await.unwrap();
+        let column = kind.xp_column();
+        let expression = match operation {
+            // Summed as NUMERIC so adding to an XP total near the INT8 limit saturates instead of failing the query.
+            XpOperation::Add(_) => format!("LEAST(member.{column}::NUMERIC + $3::INT8, 9223372036854775807)::INT8"),
+            XpOperation::Remove(_) => format!("GREATEST(member.{column} - $3, 0)"),
+            XpOperation::Reset | XpOperation::Set(_) => "$3".to_string()
+        };
+        let update_query = format!("
+            UPDATE
+                member
+            SET
+                {column} = {expression}
+            FROM
+                (SELECT member_id, {column} AS old_xp FROM member WHERE guild_id = $1 AND member_id = ANY($2) FOR UPDATE) AS old
+            WHERE
+                member.guild_id = $1
+                AND member.member_id = old.member_id
+            RETURNING
+                member.member_id,
+                old.old_xp,
+                member.{column};
+        ");
+        let audit_query = "
+            INSERT INTO xp_audit(guild_id, moderator_id, member_id, kind, operation, amount, old_xp, new_xp)
+            SELECT $1, $2, UNNEST($3::INT8[]), $4::TEXT::role_kind, $5, $6, UNNEST($7::INT8[]), UNNEST($8::INT8[]);
+        ";
+        let member_ids = member_ids.iter().map(|id| id.get() as i64).collect::<Vec<i64>>();
+
+        transaction.execute(
+            "INSERT INTO member(guild_id, member_id) SELECT $1, UNNEST($2::INT8[]) ON CONFLICT DO NOTHING;",
+            &[&(guild_id.get() as i64), &member_ids]
+        ).await.unwrap();
+
+        let rows = transaction.query(
+            &update_query,
+            &[&(guild_id.get() as i64), &member_ids, &(operation.amount() as i64)]
+        ).await.unwrap();
+        let changes = rows.into_iter().map(|row| XpChange {
+            member_id: Id::new(row.get::<_, i64>(0) as u64),
+            old_xp: row.get::<_, i64>(1) as u64,
+            new_xp: row.get::<_, i64>(2) as u64
+        }).collect::<Vec<XpChange>>();
+
+        transaction.execute(
+            audit_query,
+            &[
+                &(guild_id.get() as i64),
+                &(moderator_id.get() as i64),
+                &changes.iter().map(|change| change.member_id.get() as i64).collect::<Vec<i64>>(),
+                &kind.as_str(),
+                &operation.as_str(),
+                &(operation.amount() as i64),
+                &changes.iter().map(|change| change.old_xp as i64).collect::<Vec<i64>>(),
+                &changes.iter().map(|change| change.new_xp as i64).collect::<Vec<i64>>()
+            ]
+        ).await.unwrap();
+        transaction.commit().await.unwrap();
+
+        changes
+    }
+
+    /// Merges imported message XP into the guild's members and audits every change. With `dry_run` the changes are
+    /// worked out the same way but rolled back, so the returned summary matches what a real import would do.
+    pub async fn import_message_xp(
+        &self,
+        guild_id: Id<GuildMarker>,
+        moderator_id: Id<UserMarker>,
+        imported: &HashMap<Id<UserMarker>, u64>,
+        strategy: MergeStrategy,
+        dry_run: bool
+    ) -> Vec<XpChange> {
+        let mut client = self.get_object().await;
+        let transaction = client.transaction().await.unwrap();
+        let member_ids = imported.keys().map(|id| id.get() as i64).collect::<Vec<i64>>();
+        let existing = transaction.query(
+            "SELECT member_id, message_xp FROM member WHERE guild_id = $1 AND member_id = ANY($2) FOR UPDATE;",
+            &[&(guild_id.get() as i64), &member_ids]
+        ).await.unwrap()
+            .into_iter()
+            .map(|row| (Id::new(row.get::<_, i64>(0) as u64), row.get::<_, i64>(1) as u64))
+            .collect::<HashMap<Id<UserMarker>, u64>>();
+        let mut changes = imported
+            .iter()
+            .map(|(member_id, xp)| {
+                let old_xp = existing.get(member_id).copied().unwrap_or(0);
+
+                XpChange { member_id: *member_id, old_xp, new_xp: strategy.merge(old_xp, *xp) }
+            })
+            .filter(|change| change.old_xp != change.new_xp)
+            .collect::<Vec<XpChange>>();
+
+        changes.sort_by_key(|change| change.member_id);
+
+        if dry_run || changes.is_empty() {
+            return changes;
+        }
+
+        let update_query = "
+            INSERT INTO member(guild_id, member_id, message_xp)
+            SELECT $1, UNNEST($2::INT8[]), UNNEST($3::INT8[])
+            ON CONFLICT (guild_id, member_id) DO UPDATE SET message_xp = EXCLUDED.message_xp;
+        ";
+        let audit_query = "
+            INSERT INTO xp_audit(guild_id, moderator_id, member_id, kind, operation, amount, old_xp, new_xp)
+            SELECT $1, $2, UNNEST($3::INT8[]), 'message', 'import', UNNEST($4::INT8[]), UNNEST($5::INT8[]), UNNEST($6::INT8[]);
+        ";
+        let member_ids = changes.iter().map(|change| change.member_id.get() as i64).collect::<Vec<i64>>();
+        let new_xps = changes.iter().map(|change| change.new_xp as i64).collect::<Vec<i64>>();
+
+        transaction.execute(update_query, &[&(guild_id.get() as i64), &member_ids, &new_xps]).await.unwrap();
+        transaction.execute(
+            audit_query,
+            &[
+                &(guild_id.get() as i64),
+                &(moderator_id.get() as i64),
+                &member_ids,
+                &changes.iter().map(|change| imported[&change.member_id] as i64).collect::<Vec<i64>>(),
+                &changes.iter().map(|change| change.old_xp as i64).collect::<Vec<i64>>(),
+                &new_xps
+            ]
+        ).await.unwrap();
+        transaction.commit().await.unwrap();
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{database::test_database, levels::import::MAX_XP};
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn saturates_added_xp_at_the_storable_limit() {
+        let database = test_database().await;
+        let (guild_id, moderator_id, member_id) = (Id::new(1_000_000_009), Id::new(1), Id::new(9));
+
+        database.update_xp_with_audit(guild_id, moderator_id, &[member_id], RoleKind::Message, XpOperation::Set(MAX_XP - 5)).await;
+
+        let changes = database.update_xp_with_audit(guild_id, moderator_id, &[member_id], RoleKind::Message, XpOperation::Add(100)).await;
+
+        assert_eq!((changes[0].old_xp, changes[0].new_xp), (MAX_XP - 5, MAX_XP));
+    }
+}
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use crate::database::Database;
+use tokio_postgres::Row;
+use twilight_model::id::{Id, marker::{GenericMarker, GuildMarker}};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ModifierTarget {
+    Channel,
+    Role
+}
+
+pub struct XpModifier {
+    pub target_id: Id<GenericMarker>,
+    pub target: ModifierTarget,
+    pub multiplier: u16
+}
+
+pub struct XpEvent {
+    pub id: i64,
+    pub name: String,
+    pub multiplier: u16,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>
+}
+
+impl ModifierTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModifierTarget::Channel => "channel",
+            ModifierTarget::Role => "role"
+        }
+    }
+}
+
+impl From<Row> for XpModifier {
+    fn from(row: Row) -> Self {
+        Self {
+            target_id: Id::new(row.get::<_, i64>(0) as u64),
+            target: match row.get::<_, String>(1).as_str() {
+                "channel" => ModifierTarget::Channel,
+                _ => ModifierTarget::Role
+            },
+            multiplier: row.get::<_, i16>(2) as u16
+        }
+    }
+}
+
+impl From<Row> for XpEvent {
+    fn from(row: Row) -> Self {
+        Self {
+            id: row.get(0),
+            name: row.get(1),
+            multiplier: row.get::<_, i16>(2) as u16,
+            starts_at: row.get::<_, DateTime<Utc>>(3),
+            ends_at: row.get::<_, DateTime<Utc>>(4)
+        }
+    }
+}
+
+impl Database {
+    pub async fn create_xp_event(&self, guild_id: Id<GuildMarker>, name: String, multiplier: u16, starts_at: DateTime<Utc>, ends_at: DateTime<Utc>) -> i64 {
+        let client = self.get_object().await;
+        let query = "INSERT INTO xp_event(guild_id, name, multiplier, starts_at, ends_at) VALUES($1, $2, $3, $4, $5) RETURNING id;";
+
+        client.query_one(
+            query,
+            &[
+                &(guild_id.get() as i64),
+                &name,
+                &(multiplier as i16),
+                &starts_at,
+                &ends_at
+            ]
+        ).await.unwrap().get(0)
+    }
+
+    pub async fn delete_xp_event(&self, guild_id: Id<GuildMarker>, id: i64) -> bool {
+        let client = self.get_object().await;
+        let query = "DELETE FROM xp_event WHERE guild_id = $1 AND id = $2;";
+
+        client.execute(query, &[&(guild_id.get() as i64), &id]).await.unwrap() > 0
+    }
+
+    pub async fn delete_xp_modifier(&self, guild_id: Id<GuildMarker>, target_id: Id<GenericMarker>) -> bool {
+        let client = self.get_object().await;
+        let query = "DELETE FROM xp_modifier WHERE guild_id = $1 AND target_id = $2;";
+
+        client.execute(query, &[&(guild_id.get() as i64), &(target_id.get() as i64)]).await.unwrap() > 0
+    }
+
+    pub async fn read_active_xp_events(&self, guild_id: Id<GuildMarker>) -> Vec<XpEvent> {
+        let client = self.get_object().await;
+        let query = "
+            SELECT id, name, multiplier, starts_at, ends_at
+            FROM xp_event
+            WHERE guild_id = $1 AND starts_at <= CURRENT_TIMESTAMP AND ends_at > CURRENT_TIMESTAMP;
+        ";
+
+        client.query(query, &[&(guild_id.get() as i64)]).await.unwrap().into_iter().map(XpEvent::from).collect()
+    }
+
+    /// Reads the events that have not ended yet, soonest first.
+    pub async fn read_xp_events(&self, guild_id: Id<GuildMarker>) -> Vec<XpEvent> {
+        let client = self.get_object().await;
+        let query = "
+            SELECT id, name, multiplier, starts_at, ends_at
+            FROM xp_event
+            WHERE guild_id = $1 AND ends_at > CURRENT_TIMESTAMP
+            ORDER BY starts_at, id;
+        ";
+
+        client.query(query, &[&(guild_id.get() as i64)]).await.unwrap().into_iter().map(XpEvent::from).collect()
+    }
+
+    pub async fn read_xp_modifiers(&self, guild_id: Id<GuildMarker>) -> Vec<XpModifier> {
+        let client = self.get_object().await;
+        let query = "SELECT target_id, target, multiplier FROM xp_modifier WHERE guild_id = $1 ORDER BY target, target_id;";
+
+        client.query(query, &[&(guild_id.get() as i64)]).await.unwrap().into_iter().map(XpModifier::from).collect()
+    }
+
+    pub async fn update_xp_modifier(&self, guild_id: Id<GuildMarker>, target_id: Id<GenericMarker>, target: ModifierTarget, multiplier: u16) {
+        let client = self.get_object().await;
+        let query = "
+            INSERT INTO xp_modifier(guild_id, target_id, target, multiplier)
+            VALUES($1, $2, $3, $4)
+            ON CONFLICT (guild_id, target_id) DO UPDATE SET target = EXCLUDED.target, multiplier = EXCLUDED.multiplier;
+        ";
+
+        client.query(
+            query,
+            &[
+                &(guild_id.get() as i64),
+                &(target_id.get() as i64),
+                &target.as_str(),
+                &(multiplier as i16)
+            ]
+        ).await.unwrap();
+    }
+}
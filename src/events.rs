@@ -1,20 +1,66 @@
-use crate::util::{context::Context, helper::{handle_command, handle_component}};
+use crate::{
+    levels::{handle_message, handle_voice_state, voice},
+    roles::{handle_autorole_join, handle_autorole_screening, handle_owner_remove, handle_role_delete, reconcile_roles, restore_roles, snapshot_roles},
+    util::{context::Context, helper::{handle_command, handle_component, restore_guild_commands}}
+};
 use std::sync::Arc;
 use twilight_gateway::Event;
 use twilight_model::application::interaction::Interaction;
 
-pub async fn handle(event: Event, context: Arc<Context>) {    
+pub async fn handle(event: Event, context: Arc<Context>) {
+    let removed_roles = match &event {
+        Event::MemberRemove(member) if !member.user.bot => context
+            .cache()
+            .member(member.guild_id, member.user.id)
+            .map(|cached| cached.roles().to_vec()),
+        _ => None
+    };
+    let was_pending = match &event {
+        Event::MemberUpdate(member) => context
+            .cache()
+            .member(member.guild_id, member.user.id)
+            .is_some_and(|cached| cached.pending()),
+        _ => false
+    };
+
     context.cache().update(&event);
 
     match event {
-        Event::GuildCreate(guild) => context.database().create_setting(guild.id).await,
-        Event::GuildDelete(guild) => context.database().delete_setting(guild.id).await,
+        Event::GuildCreate(guild) => {
+            voice::restore_sessions(&context, guild.id, &guild.voice_states);
+            context.database().create_setting(guild.id).await;
+
+            if let Some(setting) = context.database().read_setting(guild.id).await {
+                restore_guild_commands(&context, &setting).await
+            }
+
+            reconcile_roles(&context, guild.id).await
+        },
+        Event::GuildDelete(guild) => {
+            voice::clear_sessions(&context, guild.id);
+            context.database().delete_setting(guild.id).await
+        },
         Event::InteractionCreate(interaction) => match interaction.0 {
             Interaction::ApplicationCommand(command) => handle_command(*command, context).await,
             Interaction::MessageComponent(component) => handle_component(*component, context).await,
             _ => {},
         },
+        Event::MemberAdd(member) => {
+            restore_roles(&context, &member.0).await;
+            handle_autorole_join(&context, &member.0).await
+        },
+        Event::MemberRemove(member) => {
+            if let Some(role_ids) = removed_roles {
+                snapshot_roles(&context, member.guild_id, member.user.id, role_ids).await
+            }
+
+            handle_owner_remove(&context, member.guild_id, member.user.id).await
+        },
+        Event::MemberUpdate(member) => handle_autorole_screening(&context, &member, was_pending).await,
+        Event::MessageCreate(message) => handle_message(message.0, &context).await,
         Event::Ready(ready) => println!("{}#{} is online!", ready.user.name, ready.user.discriminator),
+        Event::RoleDelete(role) => handle_role_delete(&context, role.guild_id, role.role_id).await,
+        Event::VoiceStateUpdate(voice_state) => handle_voice_state(voice_state.0, &context).await,
         _ => {}
     }
 
@@ -0,0 +1,33 @@
+use crate::commands::Action;
+use async_trait::async_trait;
+use super::{GifError, GifProvider};
+
+/// Asks each provider in turn, returning the first GIF found.
+pub struct ChainProvider {
+    providers: Vec<Box<dyn GifProvider>>
+}
+
+impl ChainProvider {
+    pub fn new(providers: Vec<Box<dyn GifProvider>>) -> Self {
+        Self { providers }
+    }
+}
+
+#[async_trait]
+impl GifProvider for ChainProvider {
+    async fn fetch(&self, action: Action) -> Result<String, GifError> {
+        let mut last_error = GifError::Unavailable(action.as_str());
+
+        for provider in &self.providers {
+            match provider.fetch(action).await {
+                Ok(url) => return Ok(url),
+                Err(error) => {
+                    tracing::debug!("GIF provider failed for {}: {error}", action.as_str());
+                    last_error = error;
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+}
@@ -0,0 +1,34 @@
+use crate::commands::Action;
+use async_trait::async_trait;
+use rand::{thread_rng, seq::SliceRandom};
+use std::{collections::HashMap, fs};
+use super::{GifError, GifProvider};
+
+/// Picks from a curated list of GIF URLs, read from a JSON file mapping action names to URLs, like
+/// `{"hug": ["https://..."], "pat": ["https://..."]}`.
+pub struct LocalGifProvider {
+    gifs: HashMap<String, Vec<String>>
+}
+
+impl LocalGifProvider {
+    pub fn new(gifs: HashMap<String, Vec<String>>) -> Self {
+        Self { gifs }
+    }
+
+    pub fn load(path: &str) -> Result<Self, GifError> {
+        let contents = fs::read(path)?;
+
+        Ok(Self::new(serde_json::from_slice(&contents)?))
+    }
+}
+
+#[async_trait]
+impl GifProvider for LocalGifProvider {
+    async fn fetch(&self, action: Action) -> Result<String, GifError> {
+        self.gifs
+            .get(action.as_str())
+            .and_then(|urls| urls.choose(&mut thread_rng()))
+            .cloned()
+            .ok_or(GifError::Unavailable(action.as_str()))
+    }
+}
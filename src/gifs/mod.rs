@@ -0,0 +1,53 @@
+pub mod chain;
+pub mod local;
+pub mod otaku;
+pub mod pool;
+
+pub use chain::ChainProvider;
+pub use local::LocalGifProvider;
+pub use otaku::OtakuGifProvider;
+pub use pool::GifPool;
+
+use crate::{commands::Action, constants::GIF_LIST_PATH};
+use async_trait::async_trait;
+use hyper::{Body, client::{Client, HttpConnector}};
+use hyper_tls::HttpsConnector;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum GifError {
+    #[error("the GIF request timed out")]
+    Timeout,
+    #[error("the GIF request failed: {0}")]
+    Http(#[from] hyper::Error),
+    #[error("the GIF API responded with {0}")]
+    Status(hyper::StatusCode),
+    #[error("the GIF response could not be parsed: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("the GIF list could not be read: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the GIF URL is invalid: {0}")]
+    Uri(#[from] hyper::http::uri::InvalidUri),
+    #[error("no GIF is available for {0}")]
+    Unavailable(&'static str)
+}
+
+#[async_trait]
+pub trait GifProvider: Send + Sync {
+    /// Returns the URL of a GIF showing `action`.
+    async fn fetch(&self, action: Action) -> Result<String, GifError>;
+}
+
+/// Builds the provider used by action commands: OtakuGIFs first, then the local list when one is configured.
+pub fn create_provider(client: Client<HttpsConnector<HttpConnector>, Body>) -> ChainProvider {
+    let mut providers: Vec<Box<dyn GifProvider>> = vec![Box::new(OtakuGifProvider::new(client, otaku::OTAKU_GIFS_URL))];
+
+    if let Some(path) = GIF_LIST_PATH.as_deref() {
+        match LocalGifProvider::load(path) {
+            Ok(provider) => providers.push(Box::new(provider)),
+            Err(error) => tracing::warn!("Unable to load the GIF list at {path}: {error}")
+        }
+    }
+
+    ChainProvider::new(providers)
+}
@@ -0,0 +1,120 @@
+use crate::commands::Action;
+use async_trait::async_trait;
+use hyper::{body::to_bytes, Body, client::{Client, HttpConnector}, Uri};
+use hyper_tls::HttpsConnector;
+use serde::Deserialize;
+use std::time::Duration;
+use super::{GifError, GifProvider};
+use tokio::time::timeout;
+
+pub const OTAKU_GIFS_URL: &str = "https://api.otakugifs.xyz";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Deserialize)]
+struct OtakuGifResponse {
+    url: String
+}
+
+/// Fetches GIFs from the OtakuGIFs API. The base URL is configurable so the client can be pointed at a local server.
+pub struct OtakuGifProvider {
+    base_url: String,
+    client: Client<HttpsConnector<HttpConnector>, Body>,
+    request_timeout: Duration
+}
+
+impl OtakuGifProvider {
+    pub fn new(client: Client<HttpsConnector<HttpConnector>, Body>, base_url: &str) -> Self {
+        Self { base_url: base_url.trim_end_matches('/').to_string(), client, request_timeout: REQUEST_TIMEOUT }
+    }
+
+    async fn request(&self, uri: Uri) -> Result<String, GifError> {
+        let response = self.client.get(uri).await?;
+
+        if !response.status().is_success() {
+            return Err(GifError::Status(response.status()));
+        }
+
+        let body = to_bytes(response.into_body()).await?;
+        let OtakuGifResponse { url } = serde_json::from_slice(&body)?;
+
+        Ok(url)
+    }
+}
+
+#[async_trait]
+impl GifProvider for OtakuGifProvider {
+    async fn fetch(&self, action: Action) -> Result<String, GifError> {
+        let uri = format!("{}/gif?reaction={}", self.base_url, action.as_str()).parse::<Uri>()?;
+
+        timeout(self.request_timeout, self.request(uri)).await.map_err(|_| GifError::Timeout)?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gifs::{ChainProvider, LocalGifProvider};
+    use hyper::{Response, Server, StatusCode, service::{make_service_fn, service_fn}};
+    use std::{collections::HashMap, convert::Infallible, net::SocketAddr};
+
+    /// Serves `body` with `status` on a local port after `delay`, returning the base URL to point the provider at.
+    fn serve(status: StatusCode, body: &'static str, delay: Duration) -> String {
+        let make_service = make_service_fn(move |_| async move {
+            Ok::<_, Infallible>(service_fn(move |_| async move {
+                tokio::time::sleep(delay).await;
+
+                Ok::<_, Infallible>(Response::builder().status(status).body(Body::from(body)).unwrap())
+            }))
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let base_url = format!("http://{}/", server.local_addr());
+
+        tokio::spawn(server);
+        base_url
+    }
+
+    fn create_provider(base_url: &str) -> OtakuGifProvider {
+        let mut provider = OtakuGifProvider::new(Client::builder().build(HttpsConnector::new()), base_url);
+
+        provider.request_timeout = Duration::from_millis(200);
+        provider
+    }
+
+    #[tokio::test]
+    async fn returns_the_gif_url() {
+        let base_url = serve(StatusCode::OK, r#"{"url": "https://cdn.otakugifs.xyz/gifs/hug/1.gif"}"#, Duration::ZERO);
+
+        assert_eq!(create_provider(&base_url).fetch(Action::Hug).await.unwrap(), "https://cdn.otakugifs.xyz/gifs/hug/1.gif");
+    }
+
+    #[tokio::test]
+    async fn fails_on_error_statuses() {
+        let base_url = serve(StatusCode::SERVICE_UNAVAILABLE, "", Duration::ZERO);
+
+        assert!(matches!(create_provider(&base_url).fetch(Action::Hug).await, Err(GifError::Status(StatusCode::SERVICE_UNAVAILABLE))));
+    }
+
+    #[tokio::test]
+    async fn fails_on_malformed_json() {
+        let base_url = serve(StatusCode::OK, r#"{"gif": 1}"#, Duration::ZERO);
+
+        assert!(matches!(create_provider(&base_url).fetch(Action::Hug).await, Err(GifError::Parse(_))));
+    }
+
+    #[tokio::test]
+    async fn times_out_on_slow_responses() {
+        let base_url = serve(StatusCode::OK, r#"{"url": "https://cdn.otakugifs.xyz/gifs/hug/1.gif"}"#, Duration::from_secs(5));
+
+        assert!(matches!(create_provider(&base_url).fetch(Action::Hug).await, Err(GifError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_the_local_list() {
+        let base_url = serve(StatusCode::INTERNAL_SERVER_ERROR, "", Duration::ZERO);
+        let local = LocalGifProvider::new(HashMap::from([("hug".to_string(), vec!["https://example.com/hug.gif".to_string()])]));
+        let chain = ChainProvider::new(vec![Box::new(create_provider(&base_url)), Box::new(local)]);
+
+        assert_eq!(chain.fetch(Action::Hug).await.unwrap(), "https://example.com/hug.gif");
+        assert!(matches!(chain.fetch(Action::Slap).await, Err(GifError::Unavailable("slap"))));
+    }
+}
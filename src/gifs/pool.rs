@@ -0,0 +1,158 @@
+use crate::{commands::Action, gifs::GifError, util::context::Context};
+use dashmap::{DashMap, DashSet};
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+use twilight_model::id::{Id, marker::ChannelMarker};
+
+const MAX_TRACKED_CHANNELS: usize = 10_000;
+const POOL_SIZE: usize = 8;
+const REFILL_THRESHOLD: usize = 3;
+const TICK_SECONDS: u64 = 60;
+
+/// A small buffer of GIF URLs per action so action commands can answer without waiting on a provider.
+#[derive(Default)]
+pub struct GifPool {
+    buffers: DashMap<Action, VecDeque<String>>,
+    last_shown: DashMap<Id<ChannelMarker>, String>,
+    refilling: DashSet<Action>
+}
+
+impl GifPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn len(&self, action: Action) -> usize {
+        self.buffers.get(&action).map_or(0, |buffer| buffer.len())
+    }
+
+    pub fn needs_refill(&self, action: Action) -> bool {
+        self.len(action) < REFILL_THRESHOLD
+    }
+
+    fn push(&self, action: Action, url: String) {
+        let mut buffer = self.buffers.entry(action).or_default();
+
+        if !buffer.contains(&url) {
+            buffer.push_back(url);
+        }
+    }
+
+    fn is_repeat(&self, channel_id: Id<ChannelMarker>, url: &str) -> bool {
+        self.last_shown.get(&channel_id).is_some_and(|last_url| *last_url == url)
+    }
+
+    /// Remembers the GIF shown in a channel. Only a bounded number of channels are tracked, evicting an arbitrary one
+    /// when full, since a missed repeat check only risks showing the same GIF twice.
+    fn record_shown(&self, channel_id: Id<ChannelMarker>, url: String) {
+        if self.last_shown.len() >= MAX_TRACKED_CHANNELS && !self.last_shown.contains_key(&channel_id) {
+            let evicted = self.last_shown.iter().next().map(|entry| *entry.key());
+
+            if let Some(evicted) = evicted {
+                self.last_shown.remove(&evicted);
+            }
+        }
+
+        self.last_shown.insert(channel_id, url);
+    }
+
+    /// Takes the oldest buffered GIF for the action, skipping the one last shown in the channel.
+    pub fn take(&self, action: Action, channel_id: Id<ChannelMarker>) -> Option<String> {
+        let last_shown = self.last_shown.get(&channel_id).map(|url| url.clone());
+        let url = {
+            let mut buffer = self.buffers.entry(action).or_default();
+            let position = buffer.iter().position(|url| Some(url) != last_shown.as_ref())?;
+
+            buffer.remove(position)?
+        };
+
+        self.record_shown(channel_id, url.clone());
+
+        Some(url)
+    }
+}
+
+/// Tops up the action's buffer from the GIF providers. Only one refill runs per action at a time, and it stops at the
+/// first provider error so an unavailable API is not hammered.
+pub async fn refill(context: Arc<Context>, action: Action) {
+    let pool = context.gif_pool();
+
+    if !pool.refilling.insert(action) {
+        return;
+    }
+
+    for _ in 0..POOL_SIZE * 2 {
+        if pool.len(action) >= POOL_SIZE {
+            break;
+        }
+
+        match context.gifs().fetch(action).await {
+            Ok(url) => pool.push(action, url),
+            Err(error) => {
+                tracing::debug!("Unable to refill the {} GIF pool: {error}", action.as_str());
+                break;
+            }
+        }
+    }
+
+    pool.refilling.remove(&action);
+}
+
+/// Fetches a GIF straight from the providers for when the buffer ran dry. A GIF repeating the one last shown in the
+/// channel is fetched again once, and whichever GIF is returned is recorded as shown.
+pub async fn fetch_uncached(context: &Arc<Context>, action: Action, channel_id: Id<ChannelMarker>) -> Result<String, GifError> {
+    let pool = context.gif_pool();
+    let mut url = context.gifs().fetch(action).await?;
+
+    if pool.is_repeat(channel_id, &url) {
+        if let Ok(other_url) = context.gifs().fetch(action).await {
+            url = other_url;
+        }
+    }
+
+    pool.record_shown(channel_id, url.clone());
+
+    Ok(url)
+}
+
+/// Fills every action's buffer on startup and keeps topping them up in the background.
+pub async fn run(context: Arc<Context>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(TICK_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        for action in Action::ALL.into_iter().filter(|action| action.has_gif()) {
+            refill(context.clone(), action).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_the_gif_last_shown_in_the_channel() {
+        let pool = GifPool::new();
+        let channel_id = Id::new(1);
+
+        pool.push(Action::Hug, "first".to_string());
+        pool.record_shown(channel_id, "first".to_string());
+        pool.push(Action::Hug, "second".to_string());
+
+        assert_eq!(pool.take(Action::Hug, channel_id).as_deref(), Some("second"));
+        assert!(pool.is_repeat(channel_id, "second"));
+        assert_eq!(pool.take(Action::Hug, channel_id).as_deref(), Some("first"));
+    }
+
+    #[test]
+    fn tracks_a_bounded_number_of_channels() {
+        let pool = GifPool::new();
+
+        for channel_id in 1..=MAX_TRACKED_CHANNELS as u64 + 10 {
+            pool.record_shown(Id::new(channel_id), "gif".to_string());
+        }
+
+        assert_eq!(pool.last_shown.len(), MAX_TRACKED_CHANNELS);
+    }
+}
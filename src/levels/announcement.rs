@@ -0,0 +1,72 @@
+use crate::{
+    database::{Setting, level_role::RoleKind, setting::LevelUpDestination},
+    util::{context::Context, helper::create_embed}
+};
+use std::sync::Arc;
+use twilight_model::id::{Id, marker::{ChannelMarker, RoleMarker, UserMarker}};
+
+pub const MAX_DESCRIPTION_LENGTH: usize = 4096;
+pub const MAX_TEMPLATE_LENGTH: usize = 1000;
+
+/// Cuts text down to Discord's embed description limit, counted in characters.
+pub fn truncate_description(text: &str) -> String {
+    match text.char_indices().nth(MAX_DESCRIPTION_LENGTH) {
+        Some((index, _)) => text[..index].to_string(),
+        None => text.to_string()
+    }
+}
+
+/// Fills in the `{user}`, `{level}`, `{role}` and `{kind}` placeholders of a level-up template. The placeholders can
+/// expand past the template's own limit, so the result is truncated to fit an embed description.
+pub fn render_template(template: &str, member_id: Id<UserMarker>, level: u16, role_ids: &[Id<RoleMarker>], kind: RoleKind) -> String {
+    let roles = role_ids
+        .iter()
+        .map(|role_id| format!("<@&{role_id}>"))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let rendered = template
+        .replace("{user}", &format!("<@{member_id}>"))
+        .replace("{level}", &level.to_string())
+        .replace("{role}", &roles)
+        .replace("{kind}", kind.as_str());
+
+    truncate_description(&rendered)
+}
+
+pub async fn announce_level_up(
+    context: &Arc<Context>,
+    setting: &Setting,
+    member_id: Id<UserMarker>,
+    kind: RoleKind,
+    level: u16,
+    role_ids: &[Id<RoleMarker>],
+    channel_id: Option<Id<ChannelMarker>>
+) -> Result<(), anyhow::Error> {
+    let channel_id = match setting.level_up_destination {
+        LevelUpDestination::Current => match channel_id {
+            Some(channel_id) => channel_id,
+            None => return Ok(())
+        },
+        LevelUpDestination::Channel(channel_id) => channel_id,
+        LevelUpDestination::Direct => context
+            .http()
+            .create_private_channel(member_id)
+            .exec()
+            .await?
+            .model()
+            .await?
+            .id,
+        LevelUpDestination::Disabled => return Ok(())
+    };
+    let description = render_template(&setting.level_up_template, member_id, level, role_ids, kind);
+
+    context
+        .http()
+        .create_message(channel_id)
+        .embeds(&[create_embed(&description)])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+#[derive(Clone, Debug, PartialEq)]
+pub enum LevelCurve {
+    Linear,
+    Quadratic,
+    Polynomial,
+    Table(Vec<u64>)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LevelProgress {
+    pub level: u16,
+    pub xp_into_level: u64,
+    pub xp_for_level: u64
+}
+
+impl LevelCurve {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LevelCurve::Linear => "linear",
+            LevelCurve::Quadratic => "quadratic",
+            LevelCurve::Polynomial => "polynomial",
+            LevelCurve::Table(_) => "table"
+        }
+    }
+
+    pub fn from_parts(name: &str, table: Vec<u64>) -> Self {
+        match name {
+            "linear" => LevelCurve::Linear,
+            "quadratic" => LevelCurve::Quadratic,
+            "table" if !table.is_empty() => LevelCurve::Table(table),
+            _ => LevelCurve::Polynomial
+        }
+    }
+
+    /// Parses a comma separated list of strictly increasing XP thresholds, where the nth entry is the total XP needed
+    /// to reach level n.
+    pub fn parse_table(input: &str) -> Option<Vec<u64>> {
+        let table = input
+            .split(',')
+            .map(|threshold| threshold.trim().parse::<u64>().ok())
+            .collect::<Option<Vec<u64>>>()?;
+
+        if table.is_empty() || table.len() > u16::MAX as usize || table.windows(2).any(|pair| pair[0] >= pair[1]) || table[0] == 0 {
+            None
+        } else {
+            Some(table)
+        }
+    }
+
+    /// Total XP needed to reach `level` from zero.
+    pub fn xp_for_level(&self, level: u16) -> u64 {
+        let level = level as u64;
+
+        match self {
+            LevelCurve::Linear => 100 * level,
+            LevelCurve::Quadratic => 100 * level * level,
+            // MEE6 asks for 5l² + 50l + 100 XP to go from level l to l + 1, summed here in closed form.
+            LevelCurve::Polynomial => 5 * (level * level.saturating_sub(1) * (2 * level).saturating_sub(1)) / 6 + 25 * level * level.saturating_sub(1) + 100 * level,
+            LevelCurve::Table(table) => match level {
+                0 => 0,
+                _ => table.get(level as usize - 1).copied().unwrap_or(u64::MAX)
+            }
+        }
+    }
+
+    pub fn level_for_xp(&self, xp: u64) -> u16 {
+        let (mut low, mut high) = (0u16, u16::MAX);
+
+        while low < high {
+            let middle = low + (high - low).div_ceil(2);
+
+            if self.xp_for_level(middle) <= xp {
+                low = middle;
+            } else {
+                high = middle - 1;
+            }
+        }
+
+        low
+    }
+
+    pub fn progress(&self, xp: u64) -> LevelProgress {
+        let level = self.level_for_xp(xp);
+        let floor = self.xp_for_level(level);
+        // Levels past the end of a table cost u64::MAX, which counts as maxed out rather than as a real target.
+        let ceiling = match level {
+            u16::MAX => floor,
+            _ => match self.xp_for_level(level + 1) {
+                u64::MAX => floor,
+                ceiling => ceiling
+            }
+        };
+
+        LevelProgress {
+            level,
+            xp_into_level: xp - floor,
+            xp_for_level: ceiling.saturating_sub(floor)
+        }
+    }
+}
+
+impl LevelProgress {
+    /// XP still needed to reach the next level, or zero once the curve has been maxed out.
+    pub fn xp_to_next_level(&self) -> u64 {
+        self.xp_for_level.saturating_sub(self.xp_into_level)
+    }
+}
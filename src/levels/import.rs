@@ -0,0 +1,296 @@
+use crate::levels::LevelCurve;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use thiserror::Error;
+use twilight_model::id::{Id, marker::UserMarker};
+
+pub const MAX_ROWS: usize = 100_000;
+/// XP is stored as a signed 64-bit integer, so imported amounts are capped there rather than at `u64::MAX`.
+pub const MAX_XP: u64 = i64::MAX as u64;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ImportFormat {
+    Csv,
+    Json
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MergeStrategy {
+    Add,
+    Max,
+    Overwrite
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ImportError {
+    #[error("The file is empty.")]
+    Empty,
+    #[error("The file is not valid JSON: {0}")]
+    InvalidJson(String),
+    #[error("Line {line} could not be read: {reason}")]
+    InvalidRow { line: usize, reason: String },
+    #[error("Imports are limited to {MAX_ROWS} members.")]
+    TooManyRows,
+    #[error("Only JSON and CSV exports can be imported.")]
+    UnknownFormat
+}
+
+/// A single member from an export. Some bots only export levels, so `xp` is worked out from the level with the
+/// guild's curve when it is missing.
+#[derive(Debug, PartialEq)]
+pub struct ImportRow {
+    pub member_id: Id<UserMarker>,
+    pub xp: Option<u64>,
+    pub level: Option<u16>
+}
+
+#[derive(Deserialize)]
+struct Mee6Export {
+    players: Vec<Mee6Player>
+}
+
+#[derive(Deserialize)]
+struct Mee6Player {
+    id: Value,
+    xp: Option<u64>,
+    level: Option<u16>
+}
+
+impl MergeStrategy {
+    pub fn merge(&self, old_xp: u64, imported_xp: u64) -> u64 {
+        match self {
+            MergeStrategy::Add => old_xp.saturating_add(imported_xp).min(MAX_XP),
+            MergeStrategy::Max => old_xp.max(imported_xp),
+            MergeStrategy::Overwrite => imported_xp
+        }
+    }
+}
+
+impl ImportRow {
+    /// Levels past the end of a table curve cost `u64::MAX`, so level-only rows are capped to what can be stored.
+    pub fn resolve_xp(&self, curve: &LevelCurve) -> u64 {
+        match (self.xp, self.level) {
+            (Some(xp), _) => xp,
+            (None, Some(level)) => curve.xp_for_level(level).min(MAX_XP),
+            (None, None) => 0
+        }
+    }
+}
+
+impl ImportFormat {
+    /// Picks the format from the file extension, falling back to sniffing the first character.
+    pub fn detect(filename: &str, contents: &str) -> Result<Self, ImportError> {
+        let extension = filename.rsplit_once('.').map(|(_, extension)| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("json") => Ok(ImportFormat::Json),
+            Some("csv") => Ok(ImportFormat::Csv),
+            _ => match contents.trim_start().chars().next() {
+                Some('{' | '[') => Ok(ImportFormat::Json),
+                Some(character) if character.is_ascii_alphanumeric() => Ok(ImportFormat::Csv),
+                Some(_) => Err(ImportError::UnknownFormat),
+                None => Err(ImportError::Empty)
+            }
+        }
+    }
+}
+
+fn parse_member_id(value: &str) -> Option<Id<UserMarker>> {
+    value.trim().trim_matches('"').parse::<u64>().ok().and_then(Id::new_checked)
+}
+
+fn check_xp(xp: Option<u64>) -> Result<Option<u64>, String> {
+    match xp {
+        Some(xp) if xp > MAX_XP => Err(format!("{xp} is more XP than can be stored")),
+        xp => Ok(xp)
+    }
+}
+
+/// Reads a MEE6-style leaderboard, either `{"players": [...]}` or a bare array of players. IDs may be strings or
+/// numbers.
+fn parse_json(contents: &str) -> Result<Vec<ImportRow>, ImportError> {
+    let players = match serde_json::from_str::<Mee6Export>(contents) {
+        Ok(export) => export.players,
+        Err(_) => serde_json::from_str::<Vec<Mee6Player>>(contents).map_err(|error| ImportError::InvalidJson(error.to_string()))?
+    };
+
+    players
+        .into_iter()
+        .enumerate()
+        .map(|(index, player)| {
+            let member_id = match &player.id {
+                Value::String(id) => parse_member_id(id),
+                Value::Number(id) => id.as_u64().and_then(Id::new_checked),
+                _ => None
+            };
+
+            let invalid_row = |reason: String| ImportError::InvalidRow { line: index + 1, reason };
+            let member_id = member_id.ok_or_else(|| invalid_row(format!("{} is not a user ID", player.id)))?;
+            let xp = check_xp(player.xp).map_err(invalid_row)?;
+
+            Ok(ImportRow { member_id, xp, level: player.level })
+        })
+        .collect()
+}
+
+/// Reads `user_id,xp,level` rows, where the header line is optional and either `xp` or `level` may be left empty.
+fn parse_csv(contents: &str) -> Result<Vec<ImportRow>, ImportError> {
+    let mut rows = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || (index == 0 && line.to_ascii_lowercase().starts_with("user_id")) {
+            continue;
+        }
+
+        let fields = line.split(',').map(|field| field.trim().trim_matches('"')).collect::<Vec<_>>();
+        let invalid_row = |reason: String| ImportError::InvalidRow { line: index + 1, reason };
+        let member_id = parse_member_id(fields[0]).ok_or_else(|| invalid_row(format!("{} is not a user ID", fields[0])))?;
+        let xp = match fields.get(1) {
+            Some(xp) if !xp.is_empty() => Some(xp.parse::<u64>().map_err(|_| invalid_row(format!("{xp} is not a valid amount of XP")))?),
+            _ => None
+        };
+        let xp = check_xp(xp).map_err(invalid_row)?;
+        let level = match fields.get(2) {
+            Some(level) if !level.is_empty() => Some(level.parse::<u16>().map_err(|_| invalid_row(format!("{level} is not a valid level")))?),
+            _ => None
+        };
+
+        if xp.is_none() && level.is_none() {
+            return Err(invalid_row("either XP or a level is required".to_string()));
+        }
+
+        rows.push(ImportRow { member_id, xp, level });
+    }
+
+    Ok(rows)
+}
+
+pub fn parse_export(format: ImportFormat, contents: &str) -> Result<Vec<ImportRow>, ImportError> {
+    let rows = match format {
+        ImportFormat::Csv => parse_csv(contents)?,
+        ImportFormat::Json => parse_json(contents)?
+    };
+
+    match rows.len() {
+        0 => Err(ImportError::Empty),
+        length if length > MAX_ROWS => Err(ImportError::TooManyRows),
+        _ => Ok(rows)
+    }
+}
+
+/// Resolves every row to an amount of XP, keeping the highest amount when an export lists a member more than once.
+pub fn resolve_rows(rows: &[ImportRow], curve: &LevelCurve) -> HashMap<Id<UserMarker>, u64> {
+    let mut resolved = HashMap::new();
+
+    for row in rows {
+        let xp = row.resolve_xp(curve);
+
+        resolved.entry(row.member_id).and_modify(|existing: &mut u64| *existing = (*existing).max(xp)).or_insert(xp);
+    }
+
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEE6_JSON: &str = include_str!("../../assets/fixtures/mee6.json");
+    const MEE6_MALFORMED_JSON: &str = include_str!("../../assets/fixtures/mee6_malformed.json");
+    const LEVELS_CSV: &str = include_str!("../../assets/fixtures/levels.csv");
+    const LEVELS_MALFORMED_CSV: &str = include_str!("../../assets/fixtures/levels_malformed.csv");
+
+    fn row(member_id: u64, xp: Option<u64>, level: Option<u16>) -> ImportRow {
+        ImportRow { member_id: Id::new(member_id), xp, level }
+    }
+
+    #[test]
+    fn detects_formats() {
+        assert!(ImportFormat::detect("export.JSON", "") == Ok(ImportFormat::Json));
+        assert!(ImportFormat::detect("export.txt", MEE6_JSON) == Ok(ImportFormat::Json));
+        assert!(ImportFormat::detect("export", LEVELS_CSV) == Ok(ImportFormat::Csv));
+        assert!(ImportFormat::detect("export", "  ") == Err(ImportError::Empty));
+    }
+
+    #[test]
+    fn parses_mee6_json() {
+        let rows = parse_export(ImportFormat::Json, MEE6_JSON).unwrap();
+
+        assert_eq!(rows, vec![
+            row(159985870458322944, Some(125400), Some(38)),
+            row(172002275412279296, Some(9800), Some(14)),
+            row(235088799074484224, None, Some(5))
+        ]);
+    }
+
+    #[test]
+    fn rejects_malformed_json() {
+        assert_eq!(
+            parse_export(ImportFormat::Json, MEE6_MALFORMED_JSON),
+            Err(ImportError::InvalidRow { line: 2, reason: "\"not-a-user\" is not a user ID".to_string() })
+        );
+        assert!(matches!(parse_export(ImportFormat::Json, "{\"players\": "), Err(ImportError::InvalidJson(_))));
+    }
+
+    #[test]
+    fn parses_csv_with_quoted_ids_and_level_only_rows() {
+        let rows = parse_export(ImportFormat::Csv, LEVELS_CSV).unwrap();
+
+        assert_eq!(rows, vec![
+            row(159985870458322944, Some(125400), Some(38)),
+            row(172002275412279296, Some(9800), Some(14)),
+            row(235088799074484224, None, Some(5)),
+            row(235088799074484224, Some(700), None)
+        ]);
+    }
+
+    #[test]
+    fn rejects_malformed_csv() {
+        assert_eq!(
+            parse_export(ImportFormat::Csv, LEVELS_MALFORMED_CSV),
+            Err(ImportError::InvalidRow { line: 3, reason: "lots is not a valid amount of XP".to_string() })
+        );
+        assert_eq!(
+            parse_export(ImportFormat::Csv, "159985870458322944,,"),
+            Err(ImportError::InvalidRow { line: 1, reason: "either XP or a level is required".to_string() })
+        );
+        assert_eq!(parse_export(ImportFormat::Csv, "user_id,xp,level\n"), Err(ImportError::Empty));
+    }
+
+    #[test]
+    fn rejects_xp_that_cannot_be_stored() {
+        let csv = format!("159985870458322944,{}", MAX_XP + 1);
+        let json = format!("[{{\"id\": \"159985870458322944\", \"xp\": {}}}]", u64::MAX);
+
+        assert!(matches!(parse_export(ImportFormat::Csv, &csv), Err(ImportError::InvalidRow { line: 1, .. })));
+        assert!(matches!(parse_export(ImportFormat::Json, &json), Err(ImportError::InvalidRow { line: 1, .. })));
+    }
+
+    #[test]
+    fn resolves_level_only_rows_with_the_curve() {
+        let rows = parse_export(ImportFormat::Csv, LEVELS_CSV).unwrap();
+        let resolved = resolve_rows(&rows, &LevelCurve::Linear);
+
+        assert_eq!(resolved[&Id::new(159985870458322944)], 125400);
+        assert_eq!(resolved[&Id::new(235088799074484224)], 700);
+    }
+
+    #[test]
+    fn caps_levels_past_the_end_of_a_table() {
+        let curve = LevelCurve::Table(vec![100, 300]);
+
+        assert_eq!(row(159985870458322944, None, Some(2)).resolve_xp(&curve), 300);
+        assert_eq!(row(159985870458322944, None, Some(3)).resolve_xp(&curve), MAX_XP);
+    }
+
+    #[test]
+    fn merges_within_the_storable_range() {
+        assert_eq!(MergeStrategy::Add.merge(MAX_XP - 5, 10), MAX_XP);
+        assert_eq!(MergeStrategy::Add.merge(5, 10), 15);
+        assert_eq!(MergeStrategy::Max.merge(5, 10), 10);
+        assert_eq!(MergeStrategy::Overwrite.merge(50, 10), 10);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{
+    database::{Setting, level_role::RoleKind},
+    levels::{announcement::announce_level_up, roles::resync_level_roles},
+    util::context::Context
+};
+use std::sync::Arc;
+use twilight_model::id::{Id, marker::{ChannelMarker, UserMarker}};
+
+pub async fn handle_xp_change(
+    context: &Arc<Context>,
+    setting: &Setting,
+    member_id: Id<UserMarker>,
+    kind: RoleKind,
+    old_xp: u64,
+    new_xp: u64,
+    channel_id: Option<Id<ChannelMarker>>
+) {
+    let old_level = setting.level_curve.level_for_xp(old_xp);
+    let new_level = setting.level_curve.level_for_xp(new_xp);
+
+    if new_level <= old_level {
+        return;
+    }
+
+    let changes = resync_level_roles(context, setting, member_id).await;
+
+    for error in &changes.errors {
+        tracing::warn!("Unable to sync {} level roles for {member_id} in {}: {error}", kind.as_str(), setting.guild_id);
+    }
+
+    if let Err(error) = announce_level_up(context, setting, member_id, kind, new_level, &changes.added, channel_id).await {
+        tracing::warn!("Unable to announce level up for {member_id} in {}: {error}", setting.guild_id);
+    }
+}
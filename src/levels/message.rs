@@ -0,0 +1,48 @@
+use crate::{
+    database::{level_role::RoleKind, setting::Module},
+    levels::{XpModifiers, handle_xp_change, modifier::{apply_multiplier, channel_chain}},
+    util::context::Context
+};
+use chrono::{Duration, Utc};
+use rand::Rng;
+use std::sync::Arc;
+use twilight_model::channel::message::Message;
+
+const COOLDOWN_SECONDS: u32 = 60;
+const MAX_XP: u64 = 25;
+const MIN_XP: u64 = 15;
+
+pub async fn handle_message(message: Message, context: &Arc<Context>) {
+    let guild_id = match message.guild_id {
+        Some(guild_id) => guild_id,
+        None => return
+    };
+
+    if message.author.bot {
+        return;
+    }
+
+    let setting = match context.database().read_setting(guild_id).await {
+        Some(setting) if setting.message_levels_enabled && setting.is_enabled(Module::Levels) => setting,
+        _ => return
+    };
+
+    // The increment re-checks the cooldown atomically, this only skips the modifier lookups for most messages.
+    if let Some((_, updated_at, _)) = context.database().read_xp(guild_id, message.author.id).await {
+        if updated_at > Utc::now() - Duration::seconds(COOLDOWN_SECONDS as i64) {
+            return;
+        }
+    }
+
+    let role_ids = message.member.as_ref().map(|member| member.roles.clone()).unwrap_or_default();
+    let multiplier = XpModifiers::load(context, guild_id).await.multiplier(&channel_chain(context, message.channel_id), &role_ids);
+    let xp = apply_multiplier(rand::thread_rng().gen_range(MIN_XP..=MAX_XP), multiplier);
+
+    if xp == 0 {
+        return;
+    }
+
+    if let Some(new_xp) = context.database().increment_message_xp(guild_id, message.author.id, xp, COOLDOWN_SECONDS).await {
+        handle_xp_change(context, &setting, message.author.id, RoleKind::Message, new_xp - xp, new_xp, Some(message.channel_id)).await;
+    }
+}
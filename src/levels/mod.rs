@@ -0,0 +1,17 @@
+pub mod announcement;
+pub mod curve;
+pub mod import;
+pub mod level_up;
+pub mod message;
+pub mod modifier;
+pub mod rank_card;
+pub mod roles;
+pub mod sync;
+pub mod voice;
+
+pub use curve::{LevelCurve, LevelProgress};
+pub use level_up::handle_xp_change;
+pub use message::handle_message;
+pub use modifier::XpModifiers;
+pub use rank_card::RankCard;
+pub use voice::{VoiceSession, handle_voice_state};
@@ -0,0 +1,79 @@
+use crate::{
+    database::xp_modifier::{ModifierTarget, XpEvent, XpModifier},
+    util::context::Context
+};
+use std::sync::Arc;
+use twilight_model::id::{Id, marker::{ChannelMarker, GuildMarker, RoleMarker}};
+
+/// Multipliers are stored as percentages, so 150 is 1.5x and 0 means no XP at all.
+pub const BASE_MULTIPLIER: u32 = 100;
+pub const MAX_MULTIPLIER: u32 = 500;
+
+pub struct XpModifiers {
+    modifiers: Vec<XpModifier>,
+    events: Vec<XpEvent>
+}
+
+impl XpModifiers {
+    pub async fn load(context: &Arc<Context>, guild_id: Id<GuildMarker>) -> Self {
+        Self {
+            modifiers: context.database().read_xp_modifiers(guild_id).await,
+            events: context.database().read_active_xp_events(guild_id).await
+        }
+    }
+
+    fn find(&self, target: ModifierTarget, target_id: u64) -> Option<u32> {
+        self.modifiers
+            .iter()
+            .find(|modifier| modifier.target == target && modifier.target_id.get() == target_id)
+            .map(|modifier| modifier.multiplier as u32)
+    }
+
+    /// Combines every modifier that applies to a member in a channel. `channel_ids` runs from the channel itself up
+    /// through its parents, so a thread inherits from its channel and a channel from its category.
+    ///
+    /// - A matching channel or role set to 0 blocks XP outright, whatever else applies.
+    /// - The most specific channel modifier wins over those of its parents.
+    /// - Only the highest role modifier counts, so stacking booster roles does not compound.
+    /// - Only the highest running event counts.
+    /// - The channel, role and event multipliers are then multiplied together and capped at [`MAX_MULTIPLIER`].
+    pub fn multiplier(&self, channel_ids: &[Id<ChannelMarker>], role_ids: &[Id<RoleMarker>]) -> u32 {
+        let channel_multipliers = channel_ids
+            .iter()
+            .filter_map(|channel_id| self.find(ModifierTarget::Channel, channel_id.get()))
+            .collect::<Vec<_>>();
+        let role_multipliers = role_ids
+            .iter()
+            .filter_map(|role_id| self.find(ModifierTarget::Role, role_id.get()))
+            .collect::<Vec<_>>();
+
+        if channel_multipliers.contains(&0) || role_multipliers.contains(&0) {
+            return 0;
+        }
+
+        let channel = channel_multipliers.first().copied().unwrap_or(BASE_MULTIPLIER);
+        let role = role_multipliers.into_iter().max().unwrap_or(BASE_MULTIPLIER);
+        let event = self.events.iter().map(|event| event.multiplier as u32).max().unwrap_or(BASE_MULTIPLIER);
+        let combined = channel as u64 * role as u64 * event as u64 / (BASE_MULTIPLIER as u64 * BASE_MULTIPLIER as u64);
+
+        combined.min(MAX_MULTIPLIER as u64) as u32
+    }
+}
+
+pub fn apply_multiplier(xp: u64, multiplier: u32) -> u64 {
+    xp * multiplier as u64 / BASE_MULTIPLIER as u64
+}
+
+/// Returns the channel followed by its cached parents, nearest first.
+pub fn channel_chain(context: &Arc<Context>, channel_id: Id<ChannelMarker>) -> Vec<Id<ChannelMarker>> {
+    let mut chain = vec![channel_id];
+
+    while chain.len() < 3 {
+        match context.cache().channel(*chain.last().unwrap()).and_then(|channel| channel.parent_id) {
+            Some(parent_id) => chain.push(parent_id),
+            None => break
+        }
+    }
+
+    chain
+}
@@ -0,0 +1,161 @@
+use crate::{levels::LevelProgress, util::canvas::{Canvas, Image}};
+use ab_glyph::FontRef;
+
+const FONT: &[u8] = include_bytes!("../../assets/DejaVuSans-Bold.ttf");
+const HEIGHT: u32 = 240;
+const WIDTH: u32 = 800;
+
+const BACKGROUND_COLOR: u32 = 0x23272A;
+const MUTED_COLOR: u32 = 0xB9BBBE;
+const TEXT_COLOR: u32 = 0xFFFFFF;
+const TRACK_COLOR: u32 = 0x484B4E;
+
+pub struct RankCard {
+    pub avatar: Option<Image>,
+    pub username: String,
+    pub color: u32,
+    pub message: LevelProgress,
+    pub message_rank: u64,
+    pub voice: LevelProgress,
+    pub voice_rank: u64
+}
+
+fn compact(value: u64) -> String {
+    match value {
+        0..=999 => value.to_string(),
+        1_000..=999_999 => format!("{:.1}K", value as f64 / 1_000.0),
+        _ => format!("{:.1}M", value as f64 / 1_000_000.0)
+    }
+}
+
+fn truncate(font: &FontRef, size: f32, text: &str, max_width: f32) -> String {
+    if Canvas::text_width(font, size, text) <= max_width {
+        return text.to_string();
+    }
+
+    let mut truncated = text.to_string();
+
+    while !truncated.is_empty() && Canvas::text_width(font, size, &format!("{truncated}…")) > max_width {
+        truncated.pop();
+    }
+
+    format!("{truncated}…")
+}
+
+impl RankCard {
+    fn draw_progress(&self, canvas: &mut Canvas, font: &FontRef, label: &str, progress: &LevelProgress, rank: u64, top: f32) {
+        let (left, right) = (230.0, 770.0);
+        let heading = format!("{label}  ·  LVL {}  ·  #{rank}", progress.level);
+        let xp = format!("{} / {} XP", compact(progress.xp_into_level), compact(progress.xp_for_level));
+        let ratio = match progress.xp_for_level {
+            0 => 1.0,
+            xp_for_level => progress.xp_into_level as f32 / xp_for_level as f32
+        };
+        let filled = ((right - left) * ratio).max(if progress.xp_into_level > 0 { 22.0 } else { 0.0 });
+
+        canvas.draw_text(font, 18.0, left, top, &heading, MUTED_COLOR);
+        canvas.draw_text(font, 18.0, right - Canvas::text_width(font, 18.0, &xp), top, &xp, MUTED_COLOR);
+        canvas.fill_rounded_rect(left, top + 10.0, right - left, 22.0, 11.0, TRACK_COLOR);
+
+        if filled > 0.0 {
+            canvas.fill_rounded_rect(left, top + 10.0, filled, 22.0, 11.0, self.color);
+        }
+    }
+
+    /// Renders the card to PNG bytes. Rendering is deterministic, so identical cards always produce identical images.
+    pub fn render(&self) -> Vec<u8> {
+        let font = FontRef::try_from_slice(FONT).unwrap();
+        let mut canvas = Canvas::new(WIDTH, HEIGHT);
+
+        canvas.fill_rounded_rect(0.0, 0.0, WIDTH as f32, HEIGHT as f32, 20.0, BACKGROUND_COLOR);
+        canvas.fill_circle(120.0, 120.0, 86.0, self.color);
+
+        match &self.avatar {
+            Some(avatar) => canvas.draw_circular_image(avatar, 40.0, 40.0, 160.0),
+            None => {
+                let initial = self.username.chars().next().unwrap_or('?').to_uppercase().to_string();
+
+                canvas.fill_circle(120.0, 120.0, 80.0, BACKGROUND_COLOR);
+                canvas.draw_text(&font, 72.0, 120.0 - Canvas::text_width(&font, 72.0, &initial) / 2.0, 146.0, &initial, TEXT_COLOR);
+            }
+        }
+
+        let username = truncate(&font, 34.0, &self.username, 540.0);
+
+        canvas.draw_text(&font, 34.0, 230.0, 72.0, &username, TEXT_COLOR);
+        self.draw_progress(&mut canvas, &font, "MESSAGE", &self.message, self.message_rank, 122.0);
+        self.draw_progress(&mut canvas, &font, "VOICE", &self.voice, self.voice_rank, 188.0);
+
+        canvas.encode_png()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, path::PathBuf};
+
+    /// Snapshots are regenerated with `UPDATE_SNAPSHOTS=1 cargo test` whenever the card layout changes on purpose.
+    fn assert_snapshot(name: &str, card: &RankCard) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets/snapshots").join(format!("{name}.png"));
+        let rendered = card.render();
+
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &rendered).unwrap();
+            return;
+        }
+
+        let expected = fs::read(&path).unwrap_or_else(|_| panic!("Missing snapshot {}, run with UPDATE_SNAPSHOTS=1", path.display()));
+        let expected = Image::decode_png(&expected).unwrap();
+        let actual = Image::decode_png(&rendered).unwrap();
+
+        assert_eq!((actual.width, actual.height), (expected.width, expected.height), "{name} changed size");
+        assert!(actual.pixels == expected.pixels, "{name} no longer matches its snapshot");
+    }
+
+    fn checkerboard_avatar() -> Image {
+        let pixels = (0..64 * 64)
+            .flat_map(|index| match (index % 64 / 8 + index / 64 / 8) % 2 {
+                0 => [0xE9, 0x1E, 0x63, 0xFF],
+                _ => [0xFF, 0xFF, 0xFF, 0xFF]
+            })
+            .collect();
+
+        Image { width: 64, height: 64, pixels }
+    }
+
+    fn create_card(avatar: Option<Image>, xp_into_level: u64) -> RankCard {
+        let progress = LevelProgress { level: 12, xp_into_level, xp_for_level: 1_250 };
+
+        RankCard {
+            avatar,
+            username: "Aurora".to_string(),
+            color: 0x5865F2,
+            message: progress,
+            message_rank: 3,
+            voice: progress,
+            voice_rank: 14
+        }
+    }
+
+    #[test]
+    fn renders_without_avatar_at_no_progress() {
+        assert_snapshot("rank_card_initial_empty", &create_card(None, 0));
+    }
+
+    #[test]
+    fn renders_without_avatar_at_full_progress() {
+        assert_snapshot("rank_card_initial_full", &create_card(None, 1_250));
+    }
+
+    #[test]
+    fn renders_with_avatar_at_no_progress() {
+        assert_snapshot("rank_card_avatar_empty", &create_card(Some(checkerboard_avatar()), 0));
+    }
+
+    #[test]
+    fn renders_with_avatar_at_full_progress() {
+        assert_snapshot("rank_card_avatar_full", &create_card(Some(checkerboard_avatar()), 1_250));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{
+    database::{LevelRole, Setting, level_role::RoleKind},
+    util::{context::Context, permission::{RoleError, check_assignable}}
+};
+use std::{collections::HashSet, sync::Arc};
+use twilight_model::id::{Id, marker::{GuildMarker, RoleMarker, UserMarker}};
+
+#[derive(Default)]
+pub struct RoleChanges {
+    pub added: Vec<Id<RoleMarker>>,
+    pub removed: Vec<Id<RoleMarker>>,
+    pub errors: Vec<RoleError>
+}
+
+/// Works out which level roles a member should hold. Persistent roles stack once their level is reached, while only
+/// the highest reached non-persistent role of each kind is kept.
+pub fn expected_roles(level_roles: &[LevelRole], message_level: u16, voice_level: u16) -> HashSet<Id<RoleMarker>> {
+    let mut expected = HashSet::new();
+
+    for (kind, level) in [(RoleKind::Message, message_level), (RoleKind::Voice, voice_level)] {
+        let reached = level_roles
+            .iter()
+            .filter(|level_role| level_role.kind == kind && level_role.level <= level);
+        let highest_tier = reached
+            .clone()
+            .filter(|level_role| !level_role.is_persistent)
+            .max_by_key(|level_role| level_role.level);
+
+        expected.extend(reached.filter(|level_role| level_role.is_persistent).map(|level_role| level_role.role_id));
+        expected.extend(highest_tier.map(|level_role| level_role.role_id));
+    }
+
+    expected
+}
+
+/// Returns the level roles to add and to remove so that `current` matches `expected`, leaving unrelated roles alone.
+pub fn diff_roles(level_roles: &[LevelRole], expected: &HashSet<Id<RoleMarker>>, current: &[Id<RoleMarker>]) -> (Vec<Id<RoleMarker>>, Vec<Id<RoleMarker>>) {
+    let mut to_add = expected
+        .iter()
+        .filter(|role_id| !current.contains(role_id))
+        .copied()
+        .collect::<Vec<_>>();
+    let mut to_remove = level_roles
+        .iter()
+        .map(|level_role| level_role.role_id)
+        .filter(|role_id| current.contains(role_id) && !expected.contains(role_id))
+        .collect::<Vec<_>>();
+
+    to_add.sort();
+    to_remove.sort();
+    to_remove.dedup();
+
+    (to_add, to_remove)
+}
+
+async fn current_roles(context: &Arc<Context>, setting: &Setting, member_id: Id<UserMarker>) -> Result<Vec<Id<RoleMarker>>, RoleError> {
+    if let Some(member) = context.cache().member(setting.guild_id, member_id) {
+        return Ok(member.roles().to_vec());
+    }
+
+    let member = context
+        .http()
+        .guild_member(setting.guild_id, member_id)
+        .exec()
+        .await?
+        .model()
+        .await?;
+
+    Ok(member.roles)
+}
+
+async fn apply_role_diff(context: &Arc<Context>, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>, to_add: Vec<Id<RoleMarker>>, to_remove: Vec<Id<RoleMarker>>) -> RoleChanges {
+    let mut changes = RoleChanges::default();
+
+    for role_id in to_add {
+        let result = match check_assignable(context, guild_id, role_id) {
+            Ok(_) => context.http().add_guild_member_role(guild_id, member_id, role_id).exec().await.map_err(RoleError::from),
+            Err(error) => Err(error)
+        };
+
+        match result {
+            Ok(_) => changes.added.push(role_id),
+            Err(error) => changes.errors.push(error)
+        }
+    }
+
+    for role_id in to_remove {
+        let result = match check_assignable(context, guild_id, role_id) {
+            Ok(_) => context.http().remove_guild_member_role(guild_id, member_id, role_id).exec().await.map_err(RoleError::from),
+            Err(error) => Err(error)
+        };
+
+        match result {
+            Ok(_) => changes.removed.push(role_id),
+            Err(error) => changes.errors.push(error)
+        }
+    }
+
+    changes
+}
+
+pub async fn sync_level_roles(context: &Arc<Context>, setting: &Setting, member_id: Id<UserMarker>, message_xp: u64, voice_xp: u64) -> RoleChanges {
+    match context.database().read_level_roles(setting.guild_id).await {
+        Some(level_roles) if !level_roles.is_empty() => sync_member_level_roles(context, setting, &level_roles, member_id, message_xp, voice_xp).await,
+        _ => RoleChanges::default()
+    }
+}
+
+/// Same as [`sync_level_roles`], for callers that already hold the guild's level roles.
+pub async fn sync_member_level_roles(context: &Arc<Context>, setting: &Setting, level_roles: &[LevelRole], member_id: Id<UserMarker>, message_xp: u64, voice_xp: u64) -> RoleChanges {
+    let current = match current_roles(context, setting, member_id).await {
+        Ok(current) => current,
+        Err(error) => return RoleChanges { errors: vec![error], ..Default::default() }
+    };
+    let expected = expected_roles(
+        level_roles,
+        setting.level_curve.level_for_xp(message_xp),
+        setting.level_curve.level_for_xp(voice_xp)
+    );
+    let (to_add, to_remove) = diff_roles(level_roles, &expected, &current);
+
+    apply_role_diff(context, setting.guild_id, member_id, to_add, to_remove).await
+}
+
+/// Brings a member's level roles in line with their XP after a season reset. Unlike [`sync_level_roles`], persistent
+/// roles the member already holds are left alone even if their level is no longer reached.
+pub async fn sync_season_roles(context: &Arc<Context>, setting: &Setting, level_roles: &[LevelRole], member_id: Id<UserMarker>, current: &[Id<RoleMarker>], message_xp: u64, voice_xp: u64) -> RoleChanges {
+    let expected = expected_roles(
+        level_roles,
+        setting.level_curve.level_for_xp(message_xp),
+        setting.level_curve.level_for_xp(voice_xp)
+    );
+    let (to_add, mut to_remove) = diff_roles(level_roles, &expected, current);
+
+    to_remove.retain(|role_id| level_roles.iter().any(|level_role| level_role.role_id == *role_id && !level_role.is_persistent));
+
+    apply_role_diff(context, setting.guild_id, member_id, to_add, to_remove).await
+}
+
+/// Re-reads the member's XP and brings their level roles in line with it.
+pub async fn resync_level_roles(context: &Arc<Context>, setting: &Setting, member_id: Id<UserMarker>) -> RoleChanges {
+    let (message_xp, voice_xp) = match context.database().read_xp(setting.guild_id, member_id).await {
+        Some((message_xp, _, voice_xp)) => (message_xp, voice_xp),
+        None => (0, 0)
+    };
+
+    sync_level_roles(context, setting, member_id, message_xp, voice_xp).await
+}
@@ -0,0 +1,86 @@
+use crate::{
+    database::level_role_sync::LevelRoleSync,
+    levels::roles::sync_member_level_roles,
+    util::{context::Context, helper::create_embed}
+};
+use std::{sync::Arc, time::{Duration, Instant}};
+use twilight_model::id::{Id, marker::GuildMarker};
+
+/// How often progress is saved and shown to the moderator who started the sync.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+async fn report(context: &Arc<Context>, token: &str, description: &str) {
+    let embeds = [create_embed(description)];
+    let client = context.interaction_client();
+
+    // Interaction tokens expire after 15 minutes, so long or resumed syncs carry on without reporting.
+    if let Ok(request) = client.update_response(token).embeds(Some(&embeds)) {
+        if let Err(error) = request.exec().await {
+            tracing::debug!("Unable to report level role sync progress: {error}");
+        }
+    }
+}
+
+/// Walks the guild's members in ID order from the saved cursor, so a sync interrupted by a restart picks up where it
+/// left off. Requests go out one at a time and are queued by the HTTP client's ratelimiter.
+async fn run(context: &Arc<Context>, sync: &LevelRoleSync) -> String {
+    let guild_id = sync.guild_id;
+    let (setting, level_roles) = match context.database().read_setting(guild_id).await {
+        Some(setting) => (setting, context.database().read_level_roles(guild_id).await.unwrap_or_default()),
+        None => {
+            context.database().delete_level_role_sync(guild_id).await;
+            return "Unable to find this server's settings.".to_string();
+        }
+    };
+    let members = context.database().read_members(guild_id).await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|member| sync.cursor.is_none_or(|cursor| member.member_id > cursor))
+        .collect::<Vec<_>>();
+    let total = sync.processed + members.len() as u64;
+    let (mut processed, mut failures) = (sync.processed, sync.failures);
+    let mut reported_at = Instant::now();
+
+    for member in members {
+        let changes = sync_member_level_roles(context, &setting, &level_roles, member.member_id, member.message_xp, member.voice_xp).await;
+
+        failures += changes.errors.iter().filter(|error| !error.is_unknown_member()).count() as u64;
+        processed += 1;
+
+        if reported_at.elapsed() >= PROGRESS_INTERVAL {
+            context.database().update_level_role_sync(guild_id, member.member_id, processed, failures).await;
+            report(context, &sync.interaction_token, &format!("Syncing level roles... {processed}/{total} members done.")).await;
+            reported_at = Instant::now();
+        }
+    }
+
+    context.database().delete_level_role_sync(guild_id).await;
+
+    let mut description = format!("Level roles are in sync! Checked {processed} members.");
+
+    if failures > 0 {
+        description.push_str(&format!("\n\n:warning: {failures} role update(s) failed, check my permissions and role position."));
+    }
+
+    description
+}
+
+/// Runs a sync for the guild to completion and returns its summary, or `None` if one is already running.
+pub async fn start_sync(context: &Arc<Context>, guild_id: Id<GuildMarker>, interaction_token: &str) -> Option<String> {
+    let sync = context.database().create_level_role_sync(guild_id, interaction_token).await?;
+
+    Some(run(context, &sync).await)
+}
+
+pub async fn resume_syncs(context: Arc<Context>) {
+    for sync in context.database().read_level_role_syncs().await {
+        let context = context.clone();
+
+        tracing::info!("Resuming level role sync for {} started at {}", sync.guild_id, sync.started_at);
+        tokio::spawn(async move {
+            let description = run(&context, &sync).await;
+
+            report(&context, &sync.interaction_token, &description).await;
+        });
+    }
+}
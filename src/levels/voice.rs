@@ -0,0 +1,193 @@
+use crate::{
+    database::{Setting, level_role::RoleKind, setting::Module},
+    levels::{XpModifiers, handle_xp_change, modifier::{apply_multiplier, channel_chain}},
+    util::context::Context
+};
+use std::{collections::HashMap, sync::Arc, time::{Duration, Instant}};
+use twilight_model::{
+    id::{Id, marker::{ChannelMarker, GuildMarker, UserMarker}},
+    voice::VoiceState
+};
+
+const TICK_SECONDS: u64 = 60;
+const XP_PER_MINUTE: u64 = 10;
+
+/// Seconds of voice time to credit, per guild and member.
+type DueSeconds = HashMap<Id<GuildMarker>, Vec<(Id<UserMarker>, Id<ChannelMarker>, u64)>>;
+
+pub struct VoiceSession {
+    pub channel_id: Id<ChannelMarker>,
+    pub awarded_at: Instant
+}
+
+impl VoiceSession {
+    fn new(channel_id: Id<ChannelMarker>) -> Self {
+        Self { channel_id, awarded_at: Instant::now() }
+    }
+
+    fn elapsed_minutes(&self) -> u64 {
+        self.awarded_at.elapsed().as_secs() / 60
+    }
+
+    fn elapsed_seconds(&self) -> u64 {
+        self.awarded_at.elapsed().as_secs()
+    }
+}
+
+fn is_tracked(context: &Arc<Context>, voice_state: &VoiceState) -> Option<(Id<GuildMarker>, Id<ChannelMarker>)> {
+    let guild_id = voice_state.guild_id?;
+    let channel_id = voice_state.channel_id?;
+    let is_bot = match &voice_state.member {
+        Some(member) => member.user.bot,
+        None => context.cache().user(voice_state.user_id).is_some_and(|user| user.bot)
+    };
+    let is_afk = context.cache().guild(guild_id).is_some_and(|guild| guild.afk_channel_id() == Some(channel_id));
+
+    if is_bot || is_afk { None } else { Some((guild_id, channel_id)) }
+}
+
+async fn read_enabled_setting(context: &Arc<Context>, guild_id: Id<GuildMarker>) -> Option<Setting> {
+    match context.database().read_setting(guild_id).await {
+        Some(setting) if setting.voice_levels_enabled && setting.is_enabled(Module::Levels) => Some(setting),
+        _ => None
+    }
+}
+
+/// Credits `seconds` spent in voice, so the partial minute is paid out when a session ends, moves or is flushed.
+async fn award_xp(context: &Arc<Context>, setting: &Setting, modifiers: &XpModifiers, member_id: Id<UserMarker>, channel_id: Id<ChannelMarker>, seconds: u64) {
+    let role_ids = context
+        .cache()
+        .member(setting.guild_id, member_id)
+        .map(|member| member.roles().to_vec())
+        .unwrap_or_default();
+    let multiplier = modifiers.multiplier(&channel_chain(context, channel_id), &role_ids);
+    let xp = apply_multiplier(seconds * XP_PER_MINUTE / 60, multiplier);
+
+    if xp == 0 {
+        return;
+    }
+
+    let new_xp = context.database().increment_voice_xp(setting.guild_id, member_id, xp).await;
+
+    handle_xp_change(context, setting, member_id, RoleKind::Voice, new_xp - xp, new_xp, Some(channel_id)).await;
+}
+
+/// Credits the rest of a session that ended or moved, down to the second like `flush_sessions`.
+async fn award_seconds(context: &Arc<Context>, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>, channel_id: Id<ChannelMarker>, seconds: u64) {
+    if seconds == 0 {
+        return;
+    }
+
+    if let Some(setting) = read_enabled_setting(context, guild_id).await {
+        let modifiers = XpModifiers::load(context, guild_id).await;
+
+        award_xp(context, &setting, &modifiers, member_id, channel_id, seconds).await;
+    }
+}
+
+pub async fn handle_voice_state(voice_state: VoiceState, context: &Arc<Context>) {
+    let member_id = voice_state.user_id;
+    let (guild_id, channel_id) = match is_tracked(context, &voice_state) {
+        Some(ids) => ids,
+        None => {
+            if let Some(guild_id) = voice_state.guild_id {
+                if let Some((_, session)) = context.voice_sessions().remove(&(guild_id, member_id)) {
+                    award_seconds(context, guild_id, member_id, session.channel_id, session.elapsed_seconds()).await;
+                }
+            }
+
+            return;
+        }
+    };
+    let previous = match context.voice_sessions().get_mut(&(guild_id, member_id)) {
+        Some(mut session) if session.channel_id != channel_id => {
+            let previous = (session.channel_id, session.elapsed_seconds());
+
+            *session = VoiceSession::new(channel_id);
+            Some(previous)
+        },
+        Some(_) => None,
+        None => {
+            context.voice_sessions().insert((guild_id, member_id), VoiceSession::new(channel_id));
+            None
+        }
+    };
+
+    if let Some((previous_channel_id, seconds)) = previous {
+        award_seconds(context, guild_id, member_id, previous_channel_id, seconds).await;
+    }
+}
+
+pub fn restore_sessions(context: &Arc<Context>, guild_id: Id<GuildMarker>, voice_states: &[VoiceState]) {
+    let mut current = HashMap::new();
+
+    for voice_state in voice_states {
+        if let Some((_, channel_id)) = is_tracked(context, voice_state) {
+            current.insert(voice_state.user_id, channel_id);
+        }
+    }
+
+    // Sessions that did not survive the reconnect were already credited up to the last tick.
+    context.voice_sessions().retain(|(session_guild_id, member_id), session| {
+        *session_guild_id != guild_id || current.get(member_id) == Some(&session.channel_id)
+    });
+
+    for (member_id, channel_id) in current {
+        context.voice_sessions().entry((guild_id, member_id)).or_insert_with(|| VoiceSession::new(channel_id));
+    }
+}
+
+pub fn clear_sessions(context: &Arc<Context>, guild_id: Id<GuildMarker>) {
+    context.voice_sessions().retain(|(session_guild_id, _), _| *session_guild_id != guild_id);
+}
+
+pub async fn run(context: Arc<Context>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(TICK_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        let mut due = DueSeconds::new();
+
+        for mut session in context.voice_sessions().iter_mut() {
+            let minutes = session.elapsed_minutes();
+
+            if minutes > 0 {
+                let (guild_id, member_id) = *session.key();
+
+                session.awarded_at += Duration::from_secs(minutes * 60);
+                due.entry(guild_id).or_default().push((member_id, session.channel_id, minutes * 60));
+            }
+        }
+
+        award_due(&context, due).await;
+    }
+}
+
+async fn award_due(context: &Arc<Context>, due: DueSeconds) {
+    for (guild_id, members) in due {
+        if let Some(setting) = read_enabled_setting(context, guild_id).await {
+            let modifiers = XpModifiers::load(context, guild_id).await;
+
+            for (member_id, channel_id, seconds) in members {
+                award_xp(context, &setting, &modifiers, member_id, channel_id, seconds).await;
+            }
+        }
+    }
+}
+
+/// Credits every open session up to now, including the partial minute since the last tick. Runs on shutdown so a
+/// restart does not cost members the time since the last tick.
+pub async fn flush_sessions(context: &Arc<Context>) {
+    let mut due = DueSeconds::new();
+
+    for mut session in context.voice_sessions().iter_mut() {
+        let (guild_id, member_id) = *session.key();
+        let seconds = session.elapsed_seconds();
+
+        session.awarded_at = Instant::now();
+        due.entry(guild_id).or_default().push((member_id, session.channel_id, seconds));
+    }
+
+    award_due(context, due).await;
+}
@@ -2,6 +2,9 @@ mod commands;
 mod constants;
 mod database;
 mod events;
+mod gifs;
+mod levels;
+mod roles;
 mod util;
 
 use constants::{BOT_TOKEN, INTENTS};
@@ -27,13 +30,44 @@ async fn main() -> anyhow::Result<()> {
     context_clone.database().create_tables().await;
     util::helper::register_commands(&context).await;
 
+    tokio::spawn(gifs::pool::run(context.clone()));
+    tokio::spawn(levels::sync::resume_syncs(context.clone()));
+    tokio::spawn(levels::voice::run(context.clone()));
     tokio::spawn(async move {
         context_clone.cluster().up().await;    
     });
 
-    while let Some((_shard_id, event)) = events.next().await {
-        tokio::spawn(events::handle(event, context.clone()));
+    let shutdown = shutdown_signal();
+
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            Some((_shard_id, event)) = events.next() => {
+                tokio::spawn(events::handle(event, context.clone()));
+            },
+            _ = &mut shutdown => break,
+            else => break
+        }
     }
 
+    levels::voice::flush_sessions(&context).await;
+    context.cluster().down();
+
     Ok(())
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()).unwrap();
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {},
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await.ok();
 }
\ No newline at end of file
@@ -0,0 +1,85 @@
+use crate::{
+    database::Setting,
+    util::{context::Context, permission::check_assignable}
+};
+use std::{sync::Arc, time::Duration};
+use tokio::time::sleep;
+use twilight_model::{
+    gateway::payload::incoming::MemberUpdate,
+    guild::Member,
+    id::{Id, marker::{GuildMarker, UserMarker}}
+};
+
+/// Grants the configured member roles, skipping roles the member already holds or the bot cannot assign.
+async fn assign_member_roles(context: &Arc<Context>, setting: &Setting, member_id: Id<UserMarker>) {
+    let guild_id = setting.guild_id;
+    let held = match context.cache().member(guild_id, member_id) {
+        Some(member) => member.roles().to_vec(),
+        None => return
+    };
+
+    for role_id in setting.member_role_ids.iter().filter(|role_id| !held.contains(role_id)) {
+        if let Err(error) = check_assignable(context, guild_id, *role_id) {
+            tracing::debug!("Skipping autorole {role_id} in {guild_id}: {error}");
+            continue;
+        }
+
+        if let Err(error) = context.http().add_guild_member_role(guild_id, member_id, *role_id).exec().await {
+            tracing::warn!("Unable to give autorole {role_id} to {member_id} in {guild_id}: {error}");
+        }
+    }
+}
+
+/// Grants the member roles right away, or after the configured delay. Delayed grants re-read the settings so changes
+/// made in the meantime apply, and are dropped if the member left. They do not survive a restart.
+async fn schedule_member_roles(context: &Arc<Context>, setting: Setting, member_id: Id<UserMarker>) {
+    if setting.member_role_delay == 0 {
+        return assign_member_roles(context, &setting, member_id).await;
+    }
+
+    let context = context.clone();
+    let guild_id = setting.guild_id;
+    let delay = Duration::from_secs(setting.member_role_delay as u64);
+
+    tokio::spawn(async move {
+        sleep(delay).await;
+
+        if let Some(setting) = context.database().read_setting(guild_id).await {
+            assign_member_roles(&context, &setting, member_id).await;
+        }
+    });
+}
+
+async fn read_autorole_setting(context: &Arc<Context>, guild_id: Id<GuildMarker>) -> Option<Setting> {
+    context
+        .database()
+        .read_setting(guild_id)
+        .await
+        .filter(|setting| !setting.member_role_ids.is_empty())
+}
+
+pub async fn handle_autorole_join(context: &Arc<Context>, member: &Member) {
+    if member.user.bot {
+        return;
+    }
+
+    match read_autorole_setting(context, member.guild_id).await {
+        Some(setting) if !(setting.member_role_after_screening && member.pending) => {
+            schedule_member_roles(context, setting, member.user.id).await
+        },
+        _ => {}
+    }
+}
+
+/// Grants the member roles once a member passes membership screening. `was_pending` has to be read before the cache
+/// processes the `MemberUpdate` event.
+pub async fn handle_autorole_screening(context: &Arc<Context>, update: &MemberUpdate, was_pending: bool) {
+    if update.user.bot || !was_pending || update.pending {
+        return;
+    }
+
+    match read_autorole_setting(context, update.guild_id).await {
+        Some(setting) if setting.member_role_after_screening => schedule_member_roles(context, setting, update.user.id).await,
+        _ => {}
+    }
+}
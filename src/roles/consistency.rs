@@ -0,0 +1,132 @@
+use crate::{
+    database::Setting,
+    util::{context::Context, helper::send_log}
+};
+use std::{collections::HashSet, sync::Arc};
+use twilight_model::id::{Id, marker::{GuildMarker, RoleMarker, UserMarker}};
+
+/// Drops every reference to a role that no longer exists, returning a line per feature that referenced it.
+async fn forget_role(context: &Arc<Context>, setting: &Setting, role_id: Id<RoleMarker>) -> Vec<&'static str> {
+    let guild_id = setting.guild_id;
+    let mut forgotten = Vec::new();
+
+    if context.database().delete_level_role(guild_id, role_id).await {
+        forgotten.push("level role");
+    }
+
+    if context.database().delete_shared_role(guild_id, role_id).await {
+        forgotten.push("shared role");
+    }
+
+    if context.database().delete_xp_modifier(guild_id, role_id.cast()).await {
+        forgotten.push("XP modifier");
+    }
+
+    if setting.kept_role_denied_ids.contains(&role_id) {
+        let denied_ids = setting.kept_role_denied_ids.iter().filter(|id| **id != role_id).copied().collect::<Vec<_>>();
+
+        context.database().update_kept_role_denied_ids(guild_id, &denied_ids).await;
+        forgotten.push("kept role deny-list entry");
+    }
+
+    if setting.member_role_ids.contains(&role_id) {
+        let member_role_ids = setting.member_role_ids.iter().filter(|id| **id != role_id).copied().collect::<Vec<_>>();
+
+        context.database().update_member_role_ids(guild_id, &member_role_ids).await;
+        forgotten.push("autorole");
+    }
+
+    forgotten
+}
+
+async fn forget_roles(context: &Arc<Context>, guild_id: Id<GuildMarker>, role_ids: &[Id<RoleMarker>]) {
+    let mut setting = match context.database().read_setting(guild_id).await {
+        Some(setting) => setting,
+        None => return
+    };
+    let mut lines = Vec::new();
+
+    for role_id in role_ids {
+        let forgotten = forget_role(context, &setting, *role_id).await;
+
+        setting.kept_role_denied_ids.retain(|id| id != role_id);
+        setting.member_role_ids.retain(|id| id != role_id);
+
+        if !forgotten.is_empty() {
+            lines.push(format!("`{role_id}` ({})", forgotten.join(", ")));
+        }
+    }
+
+    if !lines.is_empty() {
+        let description = format!("Deleted roles were removed from Aurora's settings:\n{}", lines.join("\n"));
+
+        send_log(context, &setting, &description).await;
+    }
+}
+
+pub async fn handle_role_delete(context: &Arc<Context>, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>) {
+    forget_roles(context, guild_id, &[role_id]).await;
+}
+
+/// Drops a departed member from the shared roles they co-owned, deleting any shared role that is left without owners.
+pub async fn handle_owner_remove(context: &Arc<Context>, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>) {
+    let shared_roles = context.database().read_shared_roles(guild_id).await.unwrap_or_default();
+    let mut deleted = Vec::new();
+
+    for shared_role in shared_roles.into_iter().filter(|shared_role| shared_role.owner_ids.contains(&member_id)) {
+        match context.database().remove_shared_role_owner(guild_id, shared_role.role_id, member_id).await {
+            Some(owner_ids) if owner_ids.is_empty() => {},
+            _ => continue
+        }
+
+        if let Err(error) = context.http().delete_role(guild_id, shared_role.role_id).exec().await {
+            tracing::warn!("Unable to delete ownerless shared role {} in {guild_id}: {error}", shared_role.role_id);
+        }
+
+        context.database().delete_shared_role(guild_id, shared_role.role_id).await;
+        deleted.push(format!("`{}`", shared_role.role_id));
+    }
+
+    if deleted.is_empty() {
+        return;
+    }
+
+    if let Some(setting) = context.database().read_setting(guild_id).await {
+        let description = format!("<@{member_id}> left, so their ownerless shared roles were deleted: {}", deleted.join(", "));
+
+        send_log(context, &setting, &description).await;
+    }
+}
+
+/// Compares the roles referenced in the database with the guild's cached roles, cleaning up any that were deleted
+/// while Aurora was offline. Must run after the cache has processed the guild.
+pub async fn reconcile_roles(context: &Arc<Context>, guild_id: Id<GuildMarker>) {
+    let existing = match context.cache().guild_roles(guild_id) {
+        Some(role_ids) => role_ids.iter().copied().collect::<HashSet<_>>(),
+        None => return
+    };
+    let member_role_ids = context.database().read_setting(guild_id).await
+        .map(|setting| setting.member_role_ids)
+        .unwrap_or_default();
+    let mut referenced = context.database().read_level_roles(guild_id).await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|level_role| level_role.role_id)
+        .chain(
+            context.database().read_shared_roles(guild_id).await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|shared_role| shared_role.role_id)
+        )
+        .chain(member_role_ids)
+        .filter(|role_id| !existing.contains(role_id))
+        .collect::<Vec<_>>();
+
+    referenced.sort();
+    referenced.dedup();
+
+    if !referenced.is_empty() {
+        forget_roles(context, guild_id, &referenced).await;
+    }
+}
+
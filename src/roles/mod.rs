@@ -0,0 +1,7 @@
+pub mod autorole;
+pub mod consistency;
+pub mod persistence;
+
+pub use autorole::{handle_autorole_join, handle_autorole_screening};
+pub use consistency::{handle_owner_remove, handle_role_delete, reconcile_roles};
+pub use persistence::{restore_roles, snapshot_roles};
@@ -0,0 +1,51 @@
+use crate::util::{context::Context, permission::check_assignable};
+use std::sync::Arc;
+use twilight_model::{
+    guild::Member,
+    id::{Id, marker::{GuildMarker, RoleMarker, UserMarker}}
+};
+
+/// Picks the snapshotted roles that may be handed back: roles that still exist, are not on the deny-list, are not
+/// already held and that the bot is allowed to assign.
+fn restorable_roles(context: &Arc<Context>, member: &Member, snapshot: Vec<Id<RoleMarker>>, denied: &[Id<RoleMarker>]) -> Vec<Id<RoleMarker>> {
+    snapshot
+        .into_iter()
+        .filter(|role_id| *role_id != member.guild_id.cast() && !denied.contains(role_id) && !member.roles.contains(role_id))
+        .filter(|role_id| context.cache().role(*role_id).is_some())
+        .filter(|role_id| check_assignable(context, member.guild_id, *role_id).is_ok())
+        .collect()
+}
+
+/// Stores the roles a member held when leaving. `role_ids` has to be read before the cache processes the
+/// `MemberRemove` event, since the member is dropped from the cache at that point.
+pub async fn snapshot_roles(context: &Arc<Context>, guild_id: Id<GuildMarker>, member_id: Id<UserMarker>, role_ids: Vec<Id<RoleMarker>>) {
+    if role_ids.is_empty() {
+        return;
+    }
+
+    match context.database().read_setting(guild_id).await {
+        Some(setting) if setting.should_keep_roles => context.database().create_role_snapshot(guild_id, member_id, &role_ids).await,
+        _ => {}
+    }
+}
+
+pub async fn restore_roles(context: &Arc<Context>, member: &Member) {
+    if member.user.bot {
+        return;
+    }
+
+    let setting = match context.database().read_setting(member.guild_id).await {
+        Some(setting) if setting.should_keep_roles => setting,
+        _ => return
+    };
+    let snapshot = match context.database().take_role_snapshot(member.guild_id, member.user.id).await {
+        Some(snapshot) => snapshot,
+        None => return
+    };
+
+    for role_id in restorable_roles(context, member, snapshot, &setting.kept_role_denied_ids) {
+        if let Err(error) = context.http().add_guild_member_role(member.guild_id, member.user.id, role_id).exec().await {
+            tracing::warn!("Unable to restore <@&{role_id}> to {} in {}: {error}", member.user.id, member.guild_id);
+        }
+    }
+}
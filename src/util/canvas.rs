@@ -0,0 +1,174 @@
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>
+}
+
+pub struct Canvas {
+    image: Image
+}
+
+impl Image {
+    /// Decodes a PNG into 8-bit RGBA pixels, returning `None` for anything the decoder rejects.
+    pub fn decode_png(bytes: &[u8]) -> Option<Self> {
+        let mut decoder = png::Decoder::new(bytes);
+
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+        let mut reader = decoder.read_info().ok()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).ok()?;
+        let samples = &buffer[..info.buffer_size()];
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => samples.to_vec(),
+            png::ColorType::Rgb => samples.chunks_exact(3).flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255]).collect(),
+            png::ColorType::GrayscaleAlpha => samples.chunks_exact(2).flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]]).collect(),
+            png::ColorType::Grayscale => samples.iter().flat_map(|g| [*g, *g, *g, 255]).collect(),
+            png::ColorType::Indexed => return None
+        };
+
+        Some(Self { width: info.width, height: info.height, pixels })
+    }
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            image: Image { width, height, pixels: vec![0; (width * height * 4) as usize] }
+        }
+    }
+
+    fn blend(&mut self, x: i32, y: i32, color: u32, alpha: f32) {
+        if x < 0 || y < 0 || x >= self.image.width as i32 || y >= self.image.height as i32 || alpha <= 0.0 {
+            return;
+        }
+
+        let alpha = alpha.min(1.0);
+        let index = ((y as u32 * self.image.width + x as u32) * 4) as usize;
+        let pixel = &mut self.image.pixels[index..index + 4];
+        let source = [(color >> 16) as u8, (color >> 8) as u8, color as u8];
+        let destination_alpha = pixel[3] as f32 / 255.0;
+        let out_alpha = alpha + destination_alpha * (1.0 - alpha);
+
+        for channel in 0..3 {
+            let blended = (source[channel] as f32 * alpha + pixel[channel] as f32 * destination_alpha * (1.0 - alpha)) / out_alpha;
+
+            pixel[channel] = blended.round() as u8;
+        }
+
+        pixel[3] = (out_alpha * 255.0).round() as u8;
+    }
+
+    pub fn fill_circle(&mut self, center_x: f32, center_y: f32, radius: f32, color: u32) {
+        for y in (center_y - radius).floor() as i32..=(center_y + radius).ceil() as i32 {
+            for x in (center_x - radius).floor() as i32..=(center_x + radius).ceil() as i32 {
+                let distance = ((x as f32 + 0.5 - center_x).powi(2) + (y as f32 + 0.5 - center_y).powi(2)).sqrt();
+
+                self.blend(x, y, color, (radius - distance + 0.5).clamp(0.0, 1.0));
+            }
+        }
+    }
+
+    pub fn fill_rounded_rect(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32, color: u32) {
+        let radius = radius.min(width / 2.0).min(height / 2.0);
+
+        for pixel_y in y.floor() as i32..(y + height).ceil() as i32 {
+            for pixel_x in x.floor() as i32..(x + width).ceil() as i32 {
+                let (center_x, center_y) = (pixel_x as f32 + 0.5, pixel_y as f32 + 0.5);
+                let nearest_x = center_x.clamp(x + radius, x + width - radius);
+                let nearest_y = center_y.clamp(y + radius, y + height - radius);
+                let distance = ((center_x - nearest_x).powi(2) + (center_y - nearest_y).powi(2)).sqrt();
+
+                self.blend(pixel_x, pixel_y, color, (radius - distance + 0.5).clamp(0.0, 1.0));
+            }
+        }
+    }
+
+    /// Draws `image` scaled into a circle of the given diameter using nearest-neighbour sampling.
+    pub fn draw_circular_image(&mut self, image: &Image, x: f32, y: f32, diameter: f32) {
+        let radius = diameter / 2.0;
+
+        for pixel_y in 0..diameter.ceil() as i32 {
+            for pixel_x in 0..diameter.ceil() as i32 {
+                let distance = ((pixel_x as f32 + 0.5 - radius).powi(2) + (pixel_y as f32 + 0.5 - radius).powi(2)).sqrt();
+                let coverage = (radius - distance + 0.5).clamp(0.0, 1.0);
+
+                if coverage <= 0.0 {
+                    continue;
+                }
+
+                let source_x = ((pixel_x as f32 / diameter) * image.width as f32) as u32;
+                let source_y = ((pixel_y as f32 / diameter) * image.height as f32) as u32;
+                let index = ((source_y.min(image.height - 1) * image.width + source_x.min(image.width - 1)) * 4) as usize;
+                let source = &image.pixels[index..index + 4];
+                let color = (source[0] as u32) << 16 | (source[1] as u32) << 8 | source[2] as u32;
+
+                self.blend(x as i32 + pixel_x, y as i32 + pixel_y, color, coverage * source[3] as f32 / 255.0);
+            }
+        }
+    }
+
+    pub fn text_width(font: &FontRef, size: f32, text: &str) -> f32 {
+        let scaled = font.as_scaled(PxScale::from(size));
+        let mut width = 0.0;
+        let mut previous = None;
+
+        for character in text.chars() {
+            let glyph_id = scaled.glyph_id(character);
+
+            if let Some(previous) = previous {
+                width += scaled.kern(previous, glyph_id);
+            }
+
+            width += scaled.h_advance(glyph_id);
+            previous = Some(glyph_id);
+        }
+
+        width
+    }
+
+    /// Draws `text` with its baseline at `baseline`, starting from `x`.
+    pub fn draw_text(&mut self, font: &FontRef, size: f32, x: f32, baseline: f32, text: &str, color: u32) {
+        let scaled = font.as_scaled(PxScale::from(size));
+        let mut caret = x;
+        let mut previous = None;
+
+        for character in text.chars() {
+            let glyph_id = scaled.glyph_id(character);
+
+            if let Some(previous) = previous {
+                caret += scaled.kern(previous, glyph_id);
+            }
+
+            let glyph = glyph_id.with_scale_and_position(size, point(caret, baseline));
+
+            caret += scaled.h_advance(glyph_id);
+            previous = Some(glyph_id);
+
+            if let Some(outlined) = font.outline_glyph(glyph) {
+                let bounds = outlined.px_bounds();
+
+                outlined.draw(|glyph_x, glyph_y, coverage| {
+                    self.blend(bounds.min.x as i32 + glyph_x as i32, bounds.min.y as i32 + glyph_y as i32, color, coverage);
+                });
+            }
+        }
+    }
+
+    pub fn encode_png(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.image.width, self.image.height);
+
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header().unwrap();
+
+        writer.write_image_data(&self.image.pixels).unwrap();
+        writer.finish().unwrap();
+
+        bytes
+    }
+}
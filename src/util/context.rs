@@ -1,18 +1,23 @@
-use crate::{constants::APPLICATION_ID, database::Database};
+use crate::{constants::APPLICATION_ID, database::Database, gifs::{create_provider, GifPool, GifProvider}, levels::VoiceSession};
+use dashmap::DashMap;
 use hyper::{Body, client::{Client, HttpConnector}};
 use hyper_tls::HttpsConnector;
 use twilight_cache_inmemory::{InMemoryCache, ResourceType};
 use twilight_gateway::Cluster;
 use twilight_http::client::{Client as HttpClient, InteractionClient};
-use twilight_model::id::{Id, marker::ApplicationMarker};
+use twilight_model::{id::{Id, marker::{ApplicationMarker, GuildMarker, UserMarker}}, util::ImageHash};
 
 pub struct Context {
     application_id: Id<ApplicationMarker>,
+    avatars: DashMap<Id<UserMarker>, (ImageHash, Vec<u8>)>,
     cache: InMemoryCache,
     cluster: Cluster,
     database: Database,
+    gif_pool: GifPool,
+    gifs: Box<dyn GifProvider>,
     http: HttpClient,
-    hyper: Client<HttpsConnector<HttpConnector>>
+    hyper: Client<HttpsConnector<HttpConnector>>,
+    voice_sessions: DashMap<(Id<GuildMarker>, Id<UserMarker>), VoiceSession>
 }
 
 impl Context {
@@ -22,23 +27,31 @@ impl Context {
             | ResourceType::MEMBER
             | ResourceType::MESSAGE
             | ResourceType::ROLE 
-            | ResourceType::USER_CURRENT;
-        let https = HttpsConnector::new();
-        
+            | ResourceType::USER_CURRENT
+            | ResourceType::VOICE_STATE;
+        let hyper = Client::builder().build::<_, Body>(HttpsConnector::new());
+
         Self {
             application_id: *APPLICATION_ID,
+            avatars: DashMap::new(),
             cache: InMemoryCache::builder()
                 .message_cache_size(15)
                 .resource_types(resource_types)
                 .build(),
             cluster,
             database: Database::new(),
+            gif_pool: GifPool::new(),
+            gifs: Box::new(create_provider(hyper.clone())),
             http,
-            hyper: Client::builder()
-                .build::<_, Body>(https),
+            hyper,
+            voice_sessions: DashMap::new()
         }
     }
 
+    pub fn avatars(&self) -> &DashMap<Id<UserMarker>, (ImageHash, Vec<u8>)> {
+        &self.avatars
+    }
+
     pub fn cache(&self) -> &InMemoryCache {
         &self.cache
     }
@@ -51,6 +64,14 @@ impl Context {
         &self.database
     }
 
+    pub fn gif_pool(&self) -> &GifPool {
+        &self.gif_pool
+    }
+
+    pub fn gifs(&self) -> &dyn GifProvider {
+        self.gifs.as_ref()
+    }
+
     pub fn http(&self) -> &HttpClient {
         &self.http
     }
@@ -59,7 +80,11 @@ impl Context {
         &self.hyper
     }
 
-    pub fn interaction_client(&self) -> InteractionClient {
+    pub fn interaction_client(&self) -> InteractionClient<'_> {
         self.http.interaction(self.application_id)
     }
+
+    pub fn voice_sessions(&self) -> &DashMap<(Id<GuildMarker>, Id<UserMarker>), VoiceSession> {
+        &self.voice_sessions
+    }
 }
\ No newline at end of file
@@ -1,22 +1,38 @@
 use crate::{
     commands::*,
     constants::{DEVELOPMENT_GUILD_ID, ENVIRONMENT},
+    database::{CustomAction, Setting, setting::Module},
     util::context::Context
 };
+use lazy_static::lazy_static;
 use std::sync::Arc;
 use twilight_interactions::command::CreateCommand;
 use twilight_model::{
-    application::{command::Command, interaction::{ApplicationCommand, message_component::MessageComponentInteraction}},
-    channel::message::MessageFlags,
-    http::interaction::{InteractionResponse, InteractionResponseType}   
+    application::{
+        command::Command,
+        component::{action_row::ActionRow, button::{Button, ButtonStyle}, Component},
+        interaction::{
+            ApplicationCommand,
+            application_command::CommandOptionValue,
+            message_component::MessageComponentInteraction
+        }
+    },
+    channel::{embed::Embed, message::MessageFlags},
+    guild::Member,
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{Id, marker::{GuildMarker, RoleMarker, UserMarker}}
 };
 use twilight_util::builder::{embed::EmbedBuilder, InteractionResponseDataBuilder};
 
-pub fn create_interaction_response(description: &str, ephemeral: bool) -> Result<InteractionResponse, anyhow::Error> {
-    let embed = EmbedBuilder::new()
+pub fn create_embed(description: &str) -> Embed {
+    EmbedBuilder::new()
         .color(0xF8F8FF)
         .description(description)
-        .build();
+        .build()
+}
+
+pub fn create_interaction_response(description: &str, ephemeral: bool) -> Result<InteractionResponse, anyhow::Error> {
+    let embed = create_embed(description);
     let mut interaction_response = InteractionResponseDataBuilder::new()
         .embeds([embed]);
 
@@ -34,47 +50,158 @@ pub fn create_interaction_response(description: &str, ephemeral: bool) -> Result
     )
 }
 
+/// Posts a notice to the guild's log channel, falling back to its system channel.
+pub async fn send_log(context: &Arc<Context>, setting: &Setting, description: &str) {
+    let channel_id = setting.log_channel_id.or_else(|| {
+        context.cache().guild(setting.guild_id).and_then(|guild| guild.system_channel_id())
+    });
+    let channel_id = match channel_id {
+        Some(channel_id) => channel_id,
+        None => return
+    };
+    let embeds = [create_embed(description)];
+
+    match context.http().create_message(channel_id).embeds(&embeds) {
+        Ok(request) => if let Err(error) = request.exec().await {
+            tracing::warn!("Unable to send log to {channel_id} in {}: {error}", setting.guild_id);
+        },
+        Err(error) => tracing::warn!("Unable to build log for {}: {error}", setting.guild_id)
+    }
+}
+
+pub fn create_page_buttons(custom_id_prefix: &str, page: usize, page_count: usize) -> Component {
+    Component::ActionRow(ActionRow {
+        components: vec![
+            Component::Button(Button {
+                custom_id: Some(format!("{custom_id_prefix}:{}", page.saturating_sub(1))),
+                disabled: page == 0,
+                emoji: None,
+                label: Some("Previous".into()),
+                style: ButtonStyle::Secondary,
+                url: None
+            }),
+            Component::Button(Button {
+                custom_id: Some(format!("{custom_id_prefix}:{}", page + 1)),
+                disabled: page + 1 >= page_count,
+                emoji: None,
+                label: Some("Next".into()),
+                style: ButtonStyle::Secondary,
+                url: None
+            })
+        ]
+    })
+}
+
+/// Commands, or `command subcommand` pairs, that may outlast Discord's three second response window. These are
+/// acknowledged with an ephemeral deferred response first, and their result is edited in once they finish.
+const DEFERRED_COMMANDS: [&str; 3] = ["levelrole sync", "season", "xp"];
+
+fn is_deferred(command: &ApplicationCommand) -> bool {
+    let name = command.data.name.as_str();
+    let subcommand = command.data.options
+        .first()
+        .filter(|option| matches!(option.value, CommandOptionValue::SubCommand(_)))
+        .map(|option| format!("{name} {}", option.name));
+
+    DEFERRED_COMMANDS.contains(&name) || subcommand.is_some_and(|subcommand| DEFERRED_COMMANDS.contains(&subcommand.as_str()))
+}
+
+async fn read_custom_action(context: &Arc<Context>, guild_id: Option<Id<GuildMarker>>, name: &str) -> Option<CustomAction> {
+    context.database().read_custom_action(guild_id?, name).await
+}
+
 pub async fn handle_command(command: ApplicationCommand, context: Arc<Context>) {
     let ApplicationCommand { id, token, ..  } = command.clone();
+
+    // Commands that are not built in are the guild's custom actions.
+    let module = match is_builtin_command(&command.data.name) {
+        true => command_module(&command.data.name),
+        false => Some(Module::Actions)
+    };
+
+    if let Some(refusal) = check_module(&context, command.guild_id, module).await {
+        context.interaction_client().create_response(id, &token, &refusal).exec().await.unwrap();
+        return;
+    }
+
+    let is_deferred = is_deferred(&command);
+
+    if is_deferred {
+        let deferred_response = InteractionResponse {
+            data: Some(
+                InteractionResponseDataBuilder::new()
+                    .flags(MessageFlags::EPHEMERAL)
+                    .build()
+            ),
+            kind: InteractionResponseType::DeferredChannelMessageWithSource
+        };
+
+        context
+            .interaction_client()
+            .create_response(id, &token, &deferred_response)
+            .exec()
+            .await
+            .unwrap();
+    }
+
     let mut interaction_response = match command.data.name.as_str() {
         "8ball" => EightBallCommand::run(command).await,
+        "actions" => ActionsCommand::run(command, &context).await,
+        "autorole" => AutoRoleCommand::run(command, &context).await,
         "bio" => BioCommand::run(command, &context).await,
         "bite" => get_interaction_response(command, &context, Action::Bite).await,
+        "config" => ConfigCommand::run(command, &context).await,
+        "customaction" => CustomActionCommand::run(command, &context).await,
         "cuddle" => get_interaction_response(command, &context, Action::Cuddle).await,
         "handhold" => get_interaction_response(command, &context, Action::Handhold).await,
         "hug" => get_interaction_response(command, &context, Action::Hug).await,
+        "keeproles" => KeepRolesCommand::run(command, &context).await,
         "kill" => KillCommand::run(command, &context).await,
         "kiss" => get_interaction_response(command, &context, Action::Kiss).await,
+        "leaderboard" => LeaderboardCommand::run(command, &context).await,
+        "levelrole" => LevelRoleCommand::run(command, &context).await,
+        "levelup" => LevelUpCommand::run(command, &context).await,
         "pat" => get_interaction_response(command, &context, Action::Pat).await,
         "pinch" => get_interaction_response(command, &context, Action::Pinch).await,
         "poke" => get_interaction_response(command, &context, Action::Poke).await,
         "punch" => get_interaction_response(command, &context, Action::Punch).await,
+        "rank" => RankCommand::run(command, &context).await,
         "rate" => RateCommand::run(command).await,
+        "season" => SeasonCommand::run(command, &context).await,
+        "sharedrole" => SharedRoleCommand::run(command, &context).await,
         "ship" => ShipCommand::run(command, &context).await,
         "shrug" => get_interaction_response(command, &context, Action::Shrug).await,
         "slap" => get_interaction_response(command, &context, Action::Slap).await,
         "tickle" => get_interaction_response(command, &context, Action::Tickle).await,
-        name => {
-            let embed = EmbedBuilder::new()
-                .color(0xFF0000)
-                .description(format!("Received unknown command \"{name}\""))
-                .build();
-
-            Ok(
-                InteractionResponse {
-                    data: Some(
-                        InteractionResponseDataBuilder::new()
-                            .embeds([embed])
-                            .flags(MessageFlags::EPHEMERAL)
-                            .build()
-                    ),
-                    kind: InteractionResponseType::ChannelMessageWithSource
-                }
-            )
+        "xp" => XpCommand::run(command, &context).await,
+        "xpevent" => XpEventCommand::run(command, &context).await,
+        "xpmodifier" => XpModifierCommand::run(command, &context).await,
+        name => match read_custom_action(&context, command.guild_id, name).await {
+            Some(custom_action) => get_custom_interaction_response(command, &context, custom_action).await,
+            None => {
+                let embed = EmbedBuilder::new()
+                    .color(0xFF0000)
+                    .description(format!("Received unknown command \"{name}\""))
+                    .build();
+
+                Ok(
+                    InteractionResponse {
+                        data: Some(
+                            InteractionResponseDataBuilder::new()
+                                .embeds([embed])
+                                .flags(MessageFlags::EPHEMERAL)
+                                .build()
+                        ),
+                        kind: InteractionResponseType::ChannelMessageWithSource
+                    }
+                )
+            }
         }
     };
 
-    if interaction_response.is_err() {
+    if let Err(error) = &interaction_response {
+        tracing::warn!("Unable to process command: {error}");
+
         let embed = EmbedBuilder::new()
             .color(0xFF0000)
             .description("Unable to process command")
@@ -93,23 +220,88 @@ pub async fn handle_command(command: ApplicationCommand, context: Arc<Context>)
         )   
     }
 
+    if is_deferred {
+        let data = interaction_response.unwrap().data.unwrap_or_default();
+        let result = context
+            .interaction_client()
+            .update_response(&token)
+            .content(data.content.as_deref())
+            .unwrap()
+            .embeds(data.embeds.as_deref())
+            .unwrap()
+            .components(data.components.as_deref())
+            .unwrap()
+            .exec()
+            .await;
+
+        // Long-running commands may finish after the interaction token expired.
+        if let Err(error) = result {
+            tracing::warn!("Unable to edit deferred response: {error}");
+        }
+    } else {
+        context
+            .interaction_client()
+            .create_response(id, &token, &interaction_response.unwrap())
+            .exec()
+            .await
+            .unwrap();
+    }
+}
+
+pub async fn handle_component(component: MessageComponentInteraction, context: Arc<Context>) {
+    let prefix = component.data.custom_id.split(':').next().unwrap_or_default();
+
+    if let Some(refusal) = check_module(&context, component.guild_id, command_module(prefix)).await {
+        context.interaction_client().create_response(component.id, &component.token, &refusal).exec().await.unwrap();
+        return;
+    }
+
+    let mut interaction_response = match component.data.custom_id.split(':').next() {
+        Some("actions") => ActionsCommand::paginate(&component, &context).await,
+        Some("leaderboard") => LeaderboardCommand::paginate(&component, &context).await,
+        Some("levelrole") => LevelRoleCommand::paginate(&component, &context).await,
+        Some("sharedrole") => SharedRoleCommand::respond_to_invite(&component, &context).await,
+        // Ship proposals sent before buttons were namespaced still use the bare "accept" and "reject" IDs.
+        Some("ship" | "accept" | "reject") => return handle_ship_component(component, context).await,
+        _ => Err(anyhow::anyhow!("Unknown component {}", component.data.custom_id))
+    };
+
+    if interaction_response.is_err() {
+        let embed = EmbedBuilder::new()
+            .color(0xFF0000)
+            .description("Unable to process component interaction")
+            .build();
+
+        interaction_response = Ok(
+            InteractionResponse {
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .embeds([embed])
+                        .flags(MessageFlags::EPHEMERAL)
+                        .build()
+                ),
+                kind: InteractionResponseType::ChannelMessageWithSource
+            }
+        )
+    }
+
     context
         .interaction_client()
-        .create_response(id, &token, &interaction_response.unwrap())
+        .create_response(component.id, &component.token, &interaction_response.unwrap())
         .exec()
         .await
-        .unwrap();   
+        .unwrap();
 }
 
-pub async fn handle_component(component: MessageComponentInteraction, context: Arc<Context>) {
+async fn handle_ship_component(component: MessageComponentInteraction, context: Arc<Context>) {
     let interaction_response = if let Some(button_presser) = component.member {
-        if let Some(mention) = component.message.mentions.into_iter().nth(0) {
+        if let Some(mention) = component.message.mentions.into_iter().next() {
             if button_presser.user.unwrap().id.eq(&mention.id) {
                 let guild_id = component.guild_id.unwrap();
                 let t = format!("**{}** has sank the ship, it looks like it was never meant to be :pensive:", mention.name);
                 let (description, content, ephemeral) = match context.database().read_ship(guild_id, mention.id).await {
                     Some(_) => ("You are already shipped!", Some("**ALREADY SHIPPED!!**"), true),
-                    None => if component.data.custom_id == "accept" {                       
+                    None => if component.data.custom_id.rsplit(':').next() == Some("accept") {                       
                         context.database().create_ship(guild_id, component.message.interaction.unwrap().user.id, mention.id).await;
     
                         (":tada: Congrats! Your ship has sailed! :tada:", Some("**SHIPPED!!**"), false)
@@ -155,11 +347,12 @@ pub async fn handle_component(component: MessageComponentInteraction, context: A
         ) 
     };
 
-    if let Err(_) = context
+    if context
         .interaction_client()
         .create_response(component.id, &component.token, &interaction_response.unwrap())
         .exec()
         .await
+        .is_err()
     {
         context
             .http()
@@ -193,29 +386,112 @@ pub fn humanize(mut milliseconds: u64) -> String {
     duration
 }
 
+/// Lists every member of a guild through the API, which unlike the cache is guaranteed to be complete.
+pub async fn read_guild_members(context: &Arc<Context>, guild_id: Id<GuildMarker>) -> Result<Vec<Member>, anyhow::Error> {
+    let mut guild_members = Vec::new();
+    let mut after = None;
+
+    loop {
+        let mut request = context.http().guild_members(guild_id).limit(1000)?;
+
+        if let Some(after) = after {
+            request = request.after(after);
+        }
+
+        let members = request.exec().await?.models().await?;
+        let is_last_page = members.len() < 1000;
+
+        after = members.last().map(|member| member.user.id);
+        guild_members.extend(members);
+
+        if is_last_page {
+            break;
+        }
+    }
+
+    Ok(guild_members)
+}
+
+/// Lists the human members holding `role_id`, paging through the guild's member list over HTTP since the cache may
+/// only hold part of a large guild.
+pub async fn read_role_member_ids(context: &Arc<Context>, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>) -> Result<Vec<Id<UserMarker>>, anyhow::Error> {
+    let member_ids = read_guild_members(context, guild_id)
+        .await?
+        .into_iter()
+        .filter(|member| !member.user.bot && member.roles.contains(&role_id))
+        .map(|member| member.user.id)
+        .collect();
+
+    Ok(member_ids)
+}
+
+/// Every command along with the module it belongs to. Commands without a module are always available.
+fn create_commands() -> Vec<(Option<Module>, Command)> {
+    let actions = Some(Module::Actions);
+    let levels = Some(Module::Levels);
+
+    vec![
+        (actions, Action::create_action_command(Action::Bite, "30% chance to flinch the target".into())),
+        (actions, Action::create_action_command(Action::Cuddle, "Big spoon or little spoon?".into())),
+        (actions, Action::create_action_command(Action::Handhold, "In case your hand gets lonely...".into())),
+        (actions, Action::create_action_command(Action::Hug, "When you need that body heat OOF".into())),
+        (actions, Action::create_action_command(Action::Kiss, "ALL THE PDA!!!".into())),
+        (actions, Action::create_action_command(Action::Pat, ":3".into())),
+        (actions, Action::create_action_command(Action::Pinch, "Grab those other cheeks ;)".into())),
+        (actions, Action::create_action_command(Action::Poke, "👉".into())),
+        (actions, Action::create_action_command(Action::Punch, "For when someone needs to be knocked out".into())),
+        (actions, Action::create_action_command(Action::Shrug, "Meh.".into())),
+        (actions, Action::create_action_command(Action::Slap, "Time to wake them up!".into())),
+        (actions, Action::create_action_command(Action::Tickle, "You know what this is...".into())),
+        (actions, ActionsCommand::create_command().into()),
+        (None, AutoRoleCommand::create_command().into()),
+        (None, BioCommand::create_command().into()),
+        (None, ConfigCommand::create_command().into()),
+        (actions, CustomActionCommand::create_command().into()),
+        (None, EightBallCommand::create_command().into()),
+        (None, KeepRolesCommand::create_command().into()),
+        (actions, KillCommand::create_command().into()),
+        (levels, LeaderboardCommand::create_command().into()),
+        (levels, LevelRoleCommand::create_command().into()),
+        (levels, LevelUpCommand::create_command().into()),
+        (levels, RankCommand::create_command().into()),
+        (None, RateCommand::create_command().into()),
+        (levels, SeasonCommand::create_command().into()),
+        (Some(Module::SharedRoles), SharedRoleCommand::create_command().into()),
+        (None, ShipCommand::create_command().into()),
+        (levels, XpCommand::create_command().into()),
+        (levels, XpEventCommand::create_command().into()),
+        (levels, XpModifierCommand::create_command().into())
+    ]
+}
+
+lazy_static! {
+    static ref COMMANDS: Vec<(Option<Module>, Command)> = create_commands();
+}
+
+pub fn is_builtin_command(name: &str) -> bool {
+    COMMANDS.iter().any(|(_, command)| command.name == name)
+}
+
+/// The module a command, or a component whose custom ID starts with the command's name, belongs to.
+pub fn command_module(name: &str) -> Option<Module> {
+    COMMANDS
+        .iter()
+        .find(|(_, command)| command.name == name)
+        .and_then(|(module, _)| *module)
+}
+
+fn is_production() -> bool {
+    ENVIRONMENT.to_lowercase() == "production"
+}
+
+/// Registers every built-in command globally, or to the development guild in development. Only custom actions are
+/// registered per guild, by [`register_guild_commands`].
 pub async fn register_commands(context: &Arc<Context>) {
-    let commands: Vec<Command> = vec![
-        Action::create_action_command(Action::Bite, "30% chance to flinch the target".into()),
-        Action::create_action_command(Action::Cuddle, "Big spoon or little spoon?".into()),
-        Action::create_action_command(Action::Handhold, "In case your hand gets lonely...".into()),
-        Action::create_action_command(Action::Hug, "When you need that body heat OOF".into()),
-        Action::create_action_command(Action::Kiss, "ALL THE PDA!!!".into()),
-        Action::create_action_command(Action::Pat, ":3".into()),
-        Action::create_action_command(Action::Pinch, "Grab those other cheeks ;)".into()),
-        Action::create_action_command(Action::Poke, "👉".into()),
-        Action::create_action_command(Action::Punch, "For when someone needs to be knocked out".into()),
-        Action::create_action_command(Action::Shrug, "Meh.".into()),
-        Action::create_action_command(Action::Slap, "Time to wake them up!".into()),
-        Action::create_action_command(Action::Tickle, "You know what this is...".into()),
-        BioCommand::create_command().into(),
-        EightBallCommand::create_command().into(),
-        KillCommand::create_command().into(),
-        RateCommand::create_command().into(),
-        ShipCommand::create_command().into()
-    ];
     let interaction_client = context.interaction_client();
+    let commands = COMMANDS.iter().map(|(_, command)| command.clone()).collect::<Vec<Command>>();
 
-    if ENVIRONMENT.to_lowercase() == "production" {
+    if is_production() {
         interaction_client
             .set_global_commands(&commands)
             .exec()
@@ -228,4 +504,64 @@ pub async fn register_commands(context: &Arc<Context>) {
             .await
             .unwrap();
     }
-}
\ No newline at end of file
+}
+
+/// Registers a guild's custom actions, leaving them out while the actions module is disabled and the guild chose to
+/// hide disabled commands. Built-in commands are global, which Discord cannot hide per guild, so disabled modules
+/// refuse them instead. In development the built-in commands are registered to the development guild as well.
+pub async fn register_guild_commands(context: &Arc<Context>, setting: &Setting) {
+    if !is_production() && setting.guild_id != *DEVELOPMENT_GUILD_ID {
+        return;
+    }
+
+    let mut commands = match is_production() {
+        true => Vec::new(),
+        false => COMMANDS.iter().map(|(_, command)| command.clone()).collect::<Vec<Command>>()
+    };
+
+    if !setting.hide_disabled_commands || setting.is_enabled(Module::Actions) {
+        let custom_actions = context.database().read_custom_actions(setting.guild_id).await;
+
+        commands.extend(custom_actions.iter().map(create_custom_action_command));
+    }
+
+    if let Err(error) = context.interaction_client().set_guild_commands(setting.guild_id, &commands).exec().await {
+        tracing::warn!("Unable to register commands for {}: {error}", setting.guild_id);
+    }
+}
+
+/// Registers the guild's commands when it becomes available, skipping guilds that have nothing to register so a
+/// restart does not bulk-overwrite every guild's commands.
+pub async fn restore_guild_commands(context: &Arc<Context>, setting: &Setting) {
+    if is_production() && !setting.hide_disabled_commands && context.database().read_custom_actions(setting.guild_id).await.is_empty() {
+        return;
+    }
+
+    register_guild_commands(context, setting).await;
+}
+
+/// Re-registers the guild's commands in the background, after its modules or custom actions changed.
+pub async fn refresh_guild_commands(context: &Arc<Context>, guild_id: Id<GuildMarker>) {
+    if let Some(setting) = context.database().read_setting(guild_id).await {
+        let context = context.clone();
+
+        tokio::spawn(async move { register_guild_commands(&context, &setting).await });
+    }
+}
+
+/// Refuses interactions for modules the guild has disabled, returning the refusal to send.
+async fn check_module(context: &Arc<Context>, guild_id: Option<Id<GuildMarker>>, module: Option<Module>) -> Option<InteractionResponse> {
+    let module = module?;
+    let setting = context.database().read_setting(guild_id?).await?;
+
+    if setting.is_enabled(module) {
+        return None;
+    }
+
+    let description = format!(
+        "The **{}** module is disabled on this server. Someone with the **Manage Server** permission can enable it with `/config module`.",
+        module.as_str()
+    );
+
+    create_interaction_response(&description, true).ok()
+}
@@ -1,2 +1,4 @@
+pub mod canvas;
 pub mod context;
-pub mod helper;
\ No newline at end of file
+pub mod helper;
+pub mod permission;
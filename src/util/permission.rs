@@ -0,0 +1,85 @@
+use crate::util::context::Context;
+use std::sync::Arc;
+use thiserror::Error;
+use twilight_http::{error::ErrorType, response::DeserializeBodyError};
+use twilight_model::{guild::{PartialMember, Permissions}, id::{Id, marker::{GuildMarker, RoleMarker}}};
+
+#[derive(Debug, Error)]
+pub enum RoleError {
+    #[error("I need the **Manage Roles** permission to do that.")]
+    MissingPermission,
+    #[error("<@&{0}> sits above my highest role, so I cannot manage it.")]
+    Hierarchy(Id<RoleMarker>),
+    #[error("<@&{0}> is managed by an integration and cannot be assigned.")]
+    Managed(Id<RoleMarker>),
+    #[error("Discord rejected the role update: {0}")]
+    Http(Box<twilight_http::Error>),
+    #[error("Discord sent an unreadable response: {0}")]
+    Response(#[from] DeserializeBodyError)
+}
+
+impl From<twilight_http::Error> for RoleError {
+    fn from(error: twilight_http::Error) -> Self {
+        RoleError::Http(Box::new(error))
+    }
+}
+
+impl RoleError {
+    /// Whether Discord rejected the request because the member is no longer in the guild.
+    pub fn is_unknown_member(&self) -> bool {
+        match self {
+            RoleError::Http(error) => matches!(error.kind(), ErrorType::Response { status, .. } if status.get() == 404),
+            _ => false
+        }
+    }
+}
+
+pub fn has_permission(member: Option<&PartialMember>, permission: Permissions) -> bool {
+    member
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.contains(permission) || permissions.contains(Permissions::ADMINISTRATOR))
+}
+
+pub fn manage_guild_permission() -> Permissions {
+    Permissions::MANAGE_GUILD
+}
+
+pub fn manage_roles_permission() -> Permissions {
+    Permissions::MANAGE_ROLES
+}
+
+/// Checks from the cache that the bot may add or remove `role_id` from members. Roles that are not cached are assumed
+/// to be assignable and left for Discord to reject.
+pub fn check_assignable(context: &Arc<Context>, guild_id: Id<GuildMarker>, role_id: Id<RoleMarker>) -> Result<(), RoleError> {
+    let cache = context.cache();
+    let bot_id = match cache.current_user() {
+        Some(user) => user.id,
+        None => return Ok(())
+    };
+
+    if let Ok(permissions) = cache.permissions().root(bot_id, guild_id) {
+        if !permissions.contains(Permissions::MANAGE_ROLES) {
+            return Err(RoleError::MissingPermission);
+        }
+    }
+
+    let role = match cache.role(role_id) {
+        Some(role) => role,
+        None => return Ok(())
+    };
+
+    if role.managed {
+        return Err(RoleError::Managed(role_id));
+    }
+
+    let bot_position = cache
+        .member_highest_role(guild_id, bot_id)
+        .and_then(|highest_role_id| cache.role(highest_role_id).map(|highest_role| highest_role.position))
+        .unwrap_or(0);
+
+    if role.position >= bot_position {
+        Err(RoleError::Hierarchy(role_id))
+    } else {
+        Ok(())
+    }
+}